@@ -0,0 +1,187 @@
+use std::fmt;
+
+use rand::distributions::{Distribution, Uniform};
+use rand::Rng;
+use statrs::distribution::{Normal, Univariate};
+
+use crate::inference::{self, ConfidenceInterval};
+use crate::summary::{self, DistributionSummary, Observer, Statistic};
+
+// Beyond this many values, the jackknife used to estimate the BCa
+// acceleration deletes groups of values rather than single values.
+const MAX_JACKKNIFE_GROUPS: usize = 1000;
+
+#[derive(Debug)]
+pub struct BootstrapSummary {
+    statistic: Statistic,
+    level: f64,
+    estimate: f64,
+    standard_error: f64,
+    percentile: ConfidenceInterval,
+    bca: ConfidenceInterval,
+}
+
+/// Resamples the values with replacement `num_resamples` times and computes
+/// percentile and bias-corrected and accelerated (BCa) intervals for the
+/// statistic, following Efron and Tibshirani, "An Introduction to the
+/// Bootstrap" (1993).
+pub fn bootstrap(
+    values: &[f64],
+    statistic: Statistic,
+    num_resamples: usize,
+    level: f64,
+    mut rng: impl Rng,
+) -> Result<BootstrapSummary, failure::Error> {
+    let could_not_calculate = || crate::SamplersError::CouldNotCalculateSummaryStatistic {
+        name: statistic.to_string(),
+    };
+    if values.is_empty() || num_resamples == 0 {
+        return Err(could_not_calculate().into());
+    }
+    let estimate = statistic
+        .evaluate(&mut values.to_vec())
+        .ok_or_else(could_not_calculate)?;
+
+    let index = Uniform::new(0, values.len());
+    let mut resample = vec![0.0; values.len()];
+    let mut replicates = Vec::with_capacity(num_resamples);
+    for _ in 0..num_resamples {
+        resample
+            .iter_mut()
+            .for_each(|value| *value = values[index.sample(&mut rng)]);
+        if let Some(replicate) = statistic.evaluate(&mut resample) {
+            replicates.push(replicate);
+        }
+    }
+    if replicates.is_empty() {
+        return Err(could_not_calculate().into());
+    }
+    replicates.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+
+    let mut replicate_summary = DistributionSummary::default();
+    replicate_summary.observe_many(replicates.iter())?;
+    let standard_error = replicate_summary.standard_deviation().unwrap_or(0.0);
+
+    let alpha = 1.0 - level;
+    let percentile = ConfidenceInterval {
+        lower: summary::quantile(&replicates, alpha / 2.0).ok_or_else(could_not_calculate)?,
+        upper: summary::quantile(&replicates, 1.0 - alpha / 2.0).ok_or_else(could_not_calculate)?,
+    };
+
+    // The bias correction is the normal quantile of the proportion of
+    // replicates below the estimate, counting ties as half.
+    let num_below = replicates.iter().filter(|&&r| r < estimate).count() as f64;
+    let num_equal = replicates.iter().filter(|&&r| r == estimate).count() as f64;
+    let num_replicates = replicates.len() as f64;
+    let proportion_below = ((num_below + num_equal / 2.0) / num_replicates)
+        .max(0.5 / num_replicates)
+        .min(1.0 - 0.5 / num_replicates);
+    let bias_correction = inference::normal_quantile(proportion_below);
+    let acceleration = jackknife_acceleration(values, statistic);
+    let standard_normal = Normal::new(0.0, 1.0)?;
+    let adjusted_quantile = |p: f64| {
+        let z = bias_correction + inference::normal_quantile(p);
+        standard_normal.cdf(bias_correction + z / (1.0 - acceleration * z))
+    };
+    let bca = ConfidenceInterval {
+        lower: summary::quantile(&replicates, adjusted_quantile(alpha / 2.0))
+            .ok_or_else(could_not_calculate)?,
+        upper: summary::quantile(&replicates, adjusted_quantile(1.0 - alpha / 2.0))
+            .ok_or_else(could_not_calculate)?,
+    };
+
+    Ok(BootstrapSummary {
+        statistic,
+        level,
+        estimate,
+        standard_error,
+        percentile,
+        bca,
+    })
+}
+
+/// Estimates the BCa acceleration from the skewness of the jackknife
+/// (leave-one-out) values of the statistic.
+fn jackknife_acceleration(values: &[f64], statistic: Statistic) -> f64 {
+    let num_groups = values.len().min(MAX_JACKKNIFE_GROUPS);
+    let mut remaining = Vec::with_capacity(values.len());
+    let jackknife_values: Vec<f64> = (0..num_groups)
+        .filter_map(|group| {
+            remaining.clear();
+            remaining.extend(
+                values
+                    .iter()
+                    .enumerate()
+                    .filter(|(i, _value)| i % num_groups != group)
+                    .map(|(_i, &value)| value),
+            );
+            statistic.evaluate(&mut remaining)
+        })
+        .collect();
+    let jackknife_mean = summary::mean(jackknife_values.iter().cloned());
+    let (sum2, sum3) = jackknife_values
+        .iter()
+        .fold((0.0, 0.0), |(sum2, sum3), value| {
+            let deviation = jackknife_mean - value;
+            (
+                sum2 + deviation * deviation,
+                sum3 + deviation * deviation * deviation,
+            )
+        });
+    if sum2 > 0.0 {
+        sum3 / (6.0 * sum2.powf(1.5))
+    } else {
+        0.0
+    }
+}
+
+impl fmt::Display for BootstrapSummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let percent = self.level * 100.0;
+        write!(
+            f,
+            "Statistic: {}\nEstimate: {}\nStandard error: {}\nPercentile {}% interval: {}\nBCa \
+             {}% interval: {}",
+            self.statistic,
+            self.estimate,
+            self.standard_error,
+            percent,
+            self.percentile,
+            percent,
+            self.bca,
+        )
+    }
+}
+
+#[test]
+fn test_bootstrap() -> Result<(), failure::Error> {
+    use rand::SeedableRng;
+
+    let values: Vec<f64> = (1..=50).map(f64::from).collect();
+    let summary = bootstrap(
+        &values,
+        Statistic::Mean,
+        2000,
+        0.95,
+        rand::rngs::StdRng::seed_from_u64(7),
+    )?;
+    assert_eq!(summary.estimate, 25.5);
+    // The standard error of the mean is about 14.43 / sqrt(50).
+    assert!((summary.standard_error - 2.04).abs() < 0.2);
+    for interval in &[summary.percentile, summary.bca] {
+        assert!(interval.lower < 25.5 && 25.5 < interval.upper);
+        assert!((interval.upper - interval.lower - 8.0).abs() < 1.0);
+    }
+
+    let again = bootstrap(
+        &values,
+        Statistic::Mean,
+        2000,
+        0.95,
+        rand::rngs::StdRng::seed_from_u64(7),
+    )?;
+    assert_eq!(summary.bca, again.bca);
+
+    assert!(bootstrap(&[], Statistic::Median, 10, 0.95, rand::thread_rng()).is_err());
+    Ok(())
+}
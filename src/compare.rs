@@ -0,0 +1,253 @@
+use std::fmt;
+
+use crate::inference::{self, TestResult};
+use crate::summary::{self, DistributionSummary, Observer, Statistic};
+
+/// A comparison of two samples, such as the timings of two benchmark runs.
+/// Differences are reported as the second sample relative to the first.
+#[derive(Debug)]
+pub struct Comparison {
+    alpha: f64,
+    summaries: [DistributionSummary; 2],
+    medians: [f64; 2],
+    welch: Option<WelchTestResult>,
+    mann_whitney: Option<TestResult>,
+    mann_whitney_u: Option<f64>,
+    cohens_d: Option<f64>,
+    cliffs_delta: Option<f64>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct WelchTestResult {
+    pub test: TestResult,
+    pub degrees_of_freedom: f64,
+}
+
+impl Comparison {
+    pub fn new(a: &[f64], b: &[f64], alpha: f64) -> Result<Comparison, failure::Error> {
+        let mut summaries = [
+            DistributionSummary::default(),
+            DistributionSummary::default(),
+        ];
+        summaries[0].observe_many(a.iter())?;
+        summaries[1].observe_many(b.iter())?;
+        let median = |values: &[f64]| {
+            Statistic::Median
+                .evaluate(&mut values.to_vec())
+                .unwrap_or(std::f64::NAN)
+        };
+        let (mann_whitney, mann_whitney_u) = match mann_whitney_u_test(a, b) {
+            Some((test, u)) => (Some(test), Some(u)),
+            None => (None, None),
+        };
+        // Cliff's delta is P(b > a) - P(b < a), which is a rescaling of U.
+        let cliffs_delta = mann_whitney_u.map(|u| 2.0 * u / (a.len() * b.len()) as f64 - 1.0);
+        Ok(Comparison {
+            alpha,
+            welch: welch_t_test(&summaries[0], &summaries[1]),
+            cohens_d: cohens_d(&summaries[0], &summaries[1]),
+            medians: [median(a), median(b)],
+            summaries,
+            mann_whitney,
+            mann_whitney_u,
+            cliffs_delta,
+        })
+    }
+
+    /// Whether the second sample is significantly greater or less than the
+    /// first. Both tests must reject the null hypothesis at the chosen alpha
+    /// and agree on the direction of the difference.
+    fn verdict(&self) -> Verdict {
+        let (welch, mann_whitney) = match (self.welch, self.mann_whitney) {
+            (Some(welch), Some(mann_whitney)) => (welch.test, mann_whitney),
+            _ => return Verdict::Inconclusive,
+        };
+        let welch_rejects = welch.p_value < self.alpha;
+        let mann_whitney_rejects = mann_whitney.p_value < self.alpha;
+        match (welch_rejects, mann_whitney_rejects) {
+            (false, false) => Verdict::NoDifference,
+            (true, true) if (welch.statistic > 0.0) == (mann_whitney.statistic > 0.0) => {
+                if welch.statistic > 0.0 {
+                    Verdict::Greater
+                } else {
+                    Verdict::Less
+                }
+            }
+            _ => Verdict::Inconclusive,
+        }
+    }
+}
+
+enum Verdict {
+    Greater,
+    Less,
+    NoDifference,
+    Inconclusive,
+}
+
+/// Welch's unequal variances t-test for a difference in means. The statistic
+/// is positive when the mean of `b` is greater than the mean of `a`.
+pub fn welch_t_test(a: &DistributionSummary, b: &DistributionSummary) -> Option<WelchTestResult> {
+    let (na, nb) = (a.count() as f64, b.count() as f64);
+    let (va, vb) = (a.variance()? / na, b.variance()? / nb);
+    if na < 2.0 || nb < 2.0 || va + vb == 0.0 {
+        return None;
+    }
+    let statistic = (b.mean()? - a.mean()?) / (va + vb).sqrt();
+    let degrees_of_freedom = (va + vb).powi(2) / (va * va / (na - 1.0) + vb * vb / (nb - 1.0));
+    Some(WelchTestResult {
+        test: TestResult {
+            statistic,
+            p_value: inference::students_t_two_sided_p_value(statistic, degrees_of_freedom)?,
+        },
+        degrees_of_freedom,
+    })
+}
+
+/// The Mann–Whitney U test using the normal approximation with tie and
+/// continuity corrections. Returns the test with the z-score as its statistic,
+/// which is positive when values of `b` tend to be greater than values of `a`,
+/// along with the U statistic of `b`.
+pub fn mann_whitney_u_test(a: &[f64], b: &[f64]) -> Option<(TestResult, f64)> {
+    if a.is_empty() || b.is_empty() {
+        return None;
+    }
+    let (na, nb) = (a.len() as f64, b.len() as f64);
+    let n = na + nb;
+    let combined: Vec<f64> = a.iter().chain(b.iter()).cloned().collect();
+    let ranks = summary::ranks(&combined);
+    let rank_sum_b: f64 = ranks[a.len()..].iter().sum();
+    let u = rank_sum_b - nb * (nb + 1.0) / 2.0;
+
+    let mut sorted = combined;
+    sorted.sort_by(|x, y| x.partial_cmp(y).unwrap_or(std::cmp::Ordering::Equal));
+    let tie_correction: f64 = sorted
+        .iter()
+        .fold(Vec::<(f64, f64)>::new(), |mut groups, &value| {
+            match groups.last_mut() {
+                Some((last, size)) if *last == value => *size += 1.0,
+                _ => groups.push((value, 1.0)),
+            }
+            groups
+        })
+        .iter()
+        .map(|(_value, size)| size * size * size - size)
+        .sum();
+    let variance = na * nb / 12.0 * ((n + 1.0) - tie_correction / (n * (n - 1.0)));
+    if variance <= 0.0 {
+        return None;
+    }
+    let difference = u - na * nb / 2.0;
+    let corrected = (difference.abs() - 0.5).max(0.0) * difference.signum();
+    let z = corrected / variance.sqrt();
+    Some((
+        TestResult {
+            statistic: z,
+            p_value: inference::normal_two_sided_p_value(z),
+        },
+        u,
+    ))
+}
+
+/// Cohen's d, the difference in means in units of the pooled standard
+/// deviation.
+pub fn cohens_d(a: &DistributionSummary, b: &DistributionSummary) -> Option<f64> {
+    let (na, nb) = (a.count() as f64, b.count() as f64);
+    let pooled_variance =
+        ((na - 1.0) * a.variance()? + (nb - 1.0) * b.variance()?) / (na + nb - 2.0);
+    Some((b.mean()? - a.mean()?) / pooled_variance.sqrt())
+}
+
+fn relative_change(from: f64, to: f64) -> f64 {
+    (to - from) / from.abs()
+}
+
+impl fmt::Display for Comparison {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let [a, b] = &self.summaries;
+        let [median_a, median_b] = self.medians;
+        let mean_a = a.mean().unwrap_or(std::f64::NAN);
+        let mean_b = b.mean().unwrap_or(std::f64::NAN);
+        writeln!(f, "Count: {} vs {}", a.count(), b.count())?;
+        writeln!(f, "Mean: {} vs {}", mean_a, mean_b)?;
+        writeln!(f, "Median: {} vs {}", median_a, median_b)?;
+        writeln!(
+            f,
+            "Standard deviation: {} vs {}",
+            a.standard_deviation().unwrap_or(std::f64::NAN),
+            b.standard_deviation().unwrap_or(std::f64::NAN)
+        )?;
+        writeln!(
+            f,
+            "Relative change in mean: {:+.3}%",
+            100.0 * relative_change(mean_a, mean_b)
+        )?;
+        writeln!(
+            f,
+            "Relative change in median: {:+.3}%",
+            100.0 * relative_change(median_a, median_b)
+        )?;
+        match self.welch {
+            Some(welch) => writeln!(
+                f,
+                "Welch's t-test: t = {}, df = {}, p = {}",
+                welch.test.statistic, welch.degrees_of_freedom, welch.test.p_value
+            )?,
+            None => writeln!(f, "Welch's t-test: NaN")?,
+        }
+        match (self.mann_whitney, self.mann_whitney_u) {
+            (Some(test), Some(u)) => writeln!(
+                f,
+                "Mann-Whitney U test: U = {}, z = {}, p = {}",
+                u, test.statistic, test.p_value
+            )?,
+            _ => writeln!(f, "Mann-Whitney U test: NaN")?,
+        }
+        writeln!(f, "Cohen's d: {}", self.cohens_d.unwrap_or(std::f64::NAN))?;
+        writeln!(
+            f,
+            "Cliff's delta: {}",
+            self.cliffs_delta.unwrap_or(std::f64::NAN)
+        )?;
+        write!(
+            f,
+            "Verdict at alpha = {}: {}",
+            self.alpha,
+            match self.verdict() {
+                Verdict::Greater => "the second sample is greater",
+                Verdict::Less => "the second sample is less",
+                Verdict::NoDifference => "no significant difference",
+                Verdict::Inconclusive => "inconclusive",
+            }
+        )
+    }
+}
+
+#[test]
+fn test_compare() -> Result<(), failure::Error> {
+    fn approx_eq(a: f64, b: f64) -> bool {
+        const THRESHOLD: f64 = 0.0001;
+        (a - b).abs() < THRESHOLD
+    }
+
+    let a = [19.0, 22.0, 16.0, 29.0, 24.0];
+    let b = [20.0, 11.0, 17.0, 12.0];
+    let comparison = Comparison::new(&a, &b, 0.05)?;
+
+    // Matches scipy.stats.ttest_ind(b, a, equal_var=False).
+    let welch = comparison.welch.unwrap();
+    assert!(approx_eq(welch.test.statistic, -2.283148));
+    assert!(approx_eq(welch.degrees_of_freedom, 6.928837));
+    assert!(approx_eq(welch.test.p_value, 0.056751));
+
+    // Matches scipy.stats.mannwhitneyu(b, a, alternative="two-sided").
+    let mann_whitney = comparison.mann_whitney.unwrap();
+    assert!(approx_eq(mann_whitney.statistic, -1.592168));
+    assert!(approx_eq(mann_whitney.p_value, 0.111347));
+    assert!(approx_eq(comparison.cliffs_delta.unwrap(), -0.7));
+
+    assert!(approx_eq(comparison.cohens_d.unwrap(), -1.502191));
+    assert!(matches!(comparison.verdict(), Verdict::NoDifference));
+
+    Ok(())
+}
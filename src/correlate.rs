@@ -0,0 +1,275 @@
+use crate::inference::{self, TestResult};
+use crate::summary::{self, Observer};
+
+/// An implementation of online updates for the co-moment of two variables,
+/// analogous to the central moments in `summary`.
+///
+/// `comoment` is the weighted sum of products of deviations from the means:
+/// $ \sum_{i=1}^n w_i (x_i - \bar{x})(y_i - \bar{y}) $. Weights are frequency
+/// weights.
+#[derive(Clone, Debug, Default)]
+pub struct CoMomentSummary {
+    count: u64,
+    weight_sum: f64,
+    mean_x: f64,
+    mean_y: f64,
+    moment2_x: f64,
+    moment2_y: f64,
+    comoment: f64,
+}
+
+impl CoMomentSummary {
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+
+    /// The sample covariance.
+    pub fn covariance(&self) -> Option<f64> {
+        if self.weight_sum <= 1.0 {
+            return None;
+        }
+        Some(self.comoment / (self.weight_sum - 1.0))
+    }
+
+    /// Pearson's correlation coefficient.
+    pub fn pearson(&self) -> Option<f64> {
+        let denominator = (self.moment2_x * self.moment2_y).sqrt();
+        if self.count < 2 || denominator == 0.0 {
+            return None;
+        }
+        Some((self.comoment / denominator).clamp(-1.0, 1.0))
+    }
+}
+
+impl Observer<'_, (f64, f64)> for CoMomentSummary {
+    fn observe_weighted(
+        &mut self,
+        &(x, y): &(f64, f64),
+        weight: f64,
+    ) -> Result<(), failure::Error> {
+        summary::validate_weight(weight)?;
+        if weight == 0.0 {
+            return Ok(());
+        }
+        self.count += 1;
+        self.weight_sum += weight;
+        let delta_x = x - self.mean_x;
+        let delta_y = y - self.mean_y;
+        self.mean_x += delta_x * weight / self.weight_sum;
+        self.mean_y += delta_y * weight / self.weight_sum;
+        self.moment2_x += weight * delta_x * (x - self.mean_x);
+        self.moment2_y += weight * delta_y * (y - self.mean_y);
+        self.comoment += weight * delta_x * (y - self.mean_y);
+        Ok(())
+    }
+}
+
+#[test]
+fn test_co_moment_summary() -> Result<(), failure::Error> {
+    fn approx_eq(a: f64, b: f64) -> bool {
+        const THRESHOLD: f64 = 1e-9;
+        (a - b).abs() < THRESHOLD
+    }
+
+    let mut summary = CoMomentSummary::default();
+    assert_eq!(summary.covariance(), None);
+    summary.observe_many([(1.0, 2.0), (2.0, 4.5), (3.0, 5.0), (4.0, 8.5)].iter())?;
+    assert_eq!(summary.count(), 4);
+    // Matches numpy.cov and numpy.corrcoef.
+    assert!(approx_eq(summary.covariance().unwrap(), 10.0 / 3.0));
+    assert!(approx_eq(summary.pearson().unwrap(), 0.964485644341));
+
+    // Integral weights match repeating each pair.
+    let mut weighted = CoMomentSummary::default();
+    weighted.observe_weighted(&(1.0, 2.0), 2.0)?;
+    weighted.observe_weighted(&(3.0, 5.0), 1.0)?;
+    let mut repeated = CoMomentSummary::default();
+    repeated.observe_many([(1.0, 2.0), (1.0, 2.0), (3.0, 5.0)].iter())?;
+    assert!(approx_eq(
+        weighted.covariance().unwrap(),
+        repeated.covariance().unwrap()
+    ));
+
+    let mut constant = CoMomentSummary::default();
+    constant.observe_many([(1.0, 2.0), (1.0, 3.0)].iter())?;
+    assert_eq!(constant.pearson(), None);
+    Ok(())
+}
+
+/// Measures of association between two paired samples.
+#[derive(Debug)]
+pub struct Correlation {
+    pub count: u64,
+    pub covariance: Option<f64>,
+    pub pearson: Option<TestResult>,
+    pub spearman: Option<TestResult>,
+    pub kendall: Option<TestResult>,
+}
+
+impl Correlation {
+    pub fn new(xs: &[f64], ys: &[f64]) -> Result<Correlation, failure::Error> {
+        let mut summary = CoMomentSummary::default();
+        for pair in xs.iter().cloned().zip(ys.iter().cloned()) {
+            summary.observe(&pair)?;
+        }
+        let n = summary.count();
+        // Spearman's correlation is Pearson's correlation of the ranks.
+        let mut rank_summary = CoMomentSummary::default();
+        for pair in summary::ranks(xs).into_iter().zip(summary::ranks(ys)) {
+            rank_summary.observe(&pair)?;
+        }
+        Ok(Correlation {
+            count: n,
+            covariance: summary.covariance(),
+            pearson: correlation_t_test(summary.pearson(), n),
+            spearman: correlation_t_test(rank_summary.pearson(), n),
+            kendall: kendall_tau_b_test(xs, ys),
+        })
+    }
+}
+
+/// Tests a correlation coefficient r against zero using the statistic
+/// r √((n - 2) / (1 - r²)), which is t-distributed with n - 2 degrees of
+/// freedom. For Spearman's correlation this is an approximation.
+fn correlation_t_test(r: Option<f64>, count: u64) -> Option<TestResult> {
+    let r = r?;
+    if count < 3 {
+        return None;
+    }
+    let degrees_of_freedom = count as f64 - 2.0;
+    let p_value = if r.abs() >= 1.0 {
+        0.0
+    } else {
+        let t = r * (degrees_of_freedom / (1.0 - r * r)).sqrt();
+        inference::students_t_two_sided_p_value(t, degrees_of_freedom)?
+    };
+    Some(TestResult {
+        statistic: r,
+        p_value,
+    })
+}
+
+/// Kendall's tau-b, which accounts for ties, computed in O(n log n) with
+/// Knight's algorithm. The p-value uses the normal approximation to the
+/// distribution of the number of concordant minus discordant pairs, with the
+/// variance corrected for ties.
+pub fn kendall_tau_b_test(xs: &[f64], ys: &[f64]) -> Option<TestResult> {
+    let n = xs.len().min(ys.len());
+    if n < 2 {
+        return None;
+    }
+    let compare = |a: &f64, b: &f64| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal);
+    let mut pairs: Vec<(f64, f64)> = xs.iter().cloned().zip(ys.iter().cloned()).collect();
+    pairs.sort_by(|a, b| compare(&a.0, &b.0).then(compare(&a.1, &b.1)));
+
+    let x_ties = tie_sizes(pairs.iter().map(|pair| pair.0));
+    let joint_ties = tie_sizes(pairs.iter().cloned());
+    let mut sorted_ys: Vec<f64> = pairs.iter().map(|pair| pair.1).collect();
+    // Pairs ordered by x are discordant exactly where sorting by y swaps
+    // them.
+    let swaps = count_inversions(&mut sorted_ys) as f64;
+    let y_ties = tie_sizes(sorted_ys.iter().cloned());
+
+    let tied_pairs = |sizes: &[f64]| sizes.iter().map(|t| t * (t - 1.0) / 2.0).sum::<f64>();
+    let nf = n as f64;
+    let n0 = nf * (nf - 1.0) / 2.0;
+    let (n1, n2, n3) = (
+        tied_pairs(&x_ties),
+        tied_pairs(&y_ties),
+        tied_pairs(&joint_ties),
+    );
+    let concordant_minus_discordant = n0 - n1 - n2 + n3 - 2.0 * swaps;
+    let denominator = ((n0 - n1) * (n0 - n2)).sqrt();
+    if denominator == 0.0 {
+        return None;
+    }
+    let tau = concordant_minus_discordant / denominator;
+
+    let sum = |sizes: &[f64], f: &dyn Fn(f64) -> f64| sizes.iter().map(|&t| f(t)).sum::<f64>();
+    let v0 = nf * (nf - 1.0) * (2.0 * nf + 5.0);
+    let vt = sum(&x_ties, &|t| t * (t - 1.0) * (2.0 * t + 5.0));
+    let vu = sum(&y_ties, &|u| u * (u - 1.0) * (2.0 * u + 5.0));
+    let v1 = sum(&x_ties, &|t| t * (t - 1.0)) * sum(&y_ties, &|u| u * (u - 1.0))
+        / (2.0 * nf * (nf - 1.0));
+    let v2 = if n > 2 {
+        sum(&x_ties, &|t| t * (t - 1.0) * (t - 2.0)) * sum(&y_ties, &|u| u * (u - 1.0) * (u - 2.0))
+            / (9.0 * nf * (nf - 1.0) * (nf - 2.0))
+    } else {
+        0.0
+    };
+    let variance = (v0 - vt - vu) / 18.0 + v1 + v2;
+    Some(TestResult {
+        statistic: tau,
+        p_value: inference::normal_two_sided_p_value(concordant_minus_discordant / variance.sqrt()),
+    })
+}
+
+/// The sizes of the runs of equal values in sorted values.
+fn tie_sizes<T: PartialEq>(sorted_values: impl Iterator<Item = T>) -> Vec<f64> {
+    let mut sizes: Vec<(T, f64)> = Vec::new();
+    for value in sorted_values {
+        match sizes.last_mut() {
+            Some((last, size)) if *last == value => *size += 1.0,
+            _ => sizes.push((value, 1.0)),
+        }
+    }
+    sizes.into_iter().map(|(_value, size)| size).collect()
+}
+
+/// Sorts the values with a merge sort, returning the number of pairs that
+/// were out of order. Equal values are not counted.
+fn count_inversions(values: &mut [f64]) -> u64 {
+    if values.len() < 2 {
+        return 0;
+    }
+    let middle = values.len() / 2;
+    let mut inversions =
+        count_inversions(&mut values[..middle]) + count_inversions(&mut values[middle..]);
+    let mut merged = Vec::with_capacity(values.len());
+    let (mut i, mut j) = (0, middle);
+    while i < middle && j < values.len() {
+        if values[i] <= values[j] {
+            merged.push(values[i]);
+            i += 1;
+        } else {
+            // Every remaining value on the left is greater than values[j].
+            inversions += (middle - i) as u64;
+            merged.push(values[j]);
+            j += 1;
+        }
+    }
+    merged.extend_from_slice(&values[i..middle]);
+    merged.extend_from_slice(&values[j..]);
+    values.copy_from_slice(&merged);
+    inversions
+}
+
+#[test]
+fn test_correlation() -> Result<(), failure::Error> {
+    fn approx_eq(a: f64, b: f64) -> bool {
+        const THRESHOLD: f64 = 0.0001;
+        (a - b).abs() < THRESHOLD
+    }
+
+    let xs = [1.0, 2.0, 2.0, 4.0, 5.0, 6.0, 7.0, 9.0];
+    let ys = [2.0, 1.0, 4.0, 4.0, 3.0, 7.0, 9.0, 8.0];
+    let correlation = Correlation::new(&xs, &ys)?;
+    // Matches scipy.stats.pearsonr, spearmanr and kendalltau.
+    assert_eq!(correlation.count, 8);
+    assert!(approx_eq(correlation.covariance.unwrap(), 7.0));
+    let pearson = correlation.pearson.unwrap();
+    assert!(approx_eq(pearson.statistic, 0.864452));
+    assert!(approx_eq(pearson.p_value, 0.005610));
+    let spearman = correlation.spearman.unwrap();
+    assert!(approx_eq(spearman.statistic, 0.837349));
+    assert!(approx_eq(spearman.p_value, 0.009488));
+    let kendall = correlation.kendall.unwrap();
+    assert!(approx_eq(kendall.statistic, 0.666667));
+    assert!(approx_eq(kendall.p_value, 0.023749));
+
+    let mut values = [3.0, 1.0, 2.0, 2.0, 0.0];
+    assert_eq!(count_inversions(&mut values), 7);
+    assert_eq!(values, [0.0, 1.0, 2.0, 2.0, 3.0]);
+    assert_eq!(kendall_tau_b_test(&[1.0, 1.0], &[2.0, 3.0]), None);
+    Ok(())
+}
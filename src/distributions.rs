@@ -1,32 +1,269 @@
+use std::fmt;
+
+use rand::Rng;
 use rand_distr::{Distribution, Uniform};
-use statrs::distribution::{Binomial, Exponential, Normal, Poisson};
+use statrs::distribution::{
+    Binomial, Continuous, Discrete, Exponential, Gamma, LogNormal, Normal, Poisson, Univariate,
+};
+use statrs::statistics::{Max, Mean, Min, Variance};
 
-pub fn gaussian(mean: f64, variance: f64) -> Result<impl Iterator<Item = f64>, failure::Error> {
+pub fn gaussian(
+    mean: f64,
+    variance: f64,
+    rng: impl Rng,
+) -> Result<impl Iterator<Item = f64>, failure::Error> {
     let normal = Normal::new(mean, variance.sqrt())?;
-    Ok(normal.sample_iter(rand::thread_rng()))
+    Ok(normal.sample_iter(rng))
 }
 
-pub fn binomial(n: u64, p: f64) -> Result<impl Iterator<Item = f64>, failure::Error> {
+pub fn binomial(
+    n: u64,
+    p: f64,
+    rng: impl Rng,
+) -> Result<impl Iterator<Item = f64>, failure::Error> {
     let binomial = Binomial::new(p, n)?;
-    Ok(binomial.sample_iter(rand::thread_rng()))
+    Ok(binomial.sample_iter(rng))
 }
 
-pub fn poisson(lambda: f64) -> Result<impl Iterator<Item = f64>, failure::Error> {
+pub fn poisson(lambda: f64, rng: impl Rng) -> Result<impl Iterator<Item = f64>, failure::Error> {
     let poisson = Poisson::new(lambda)?;
-    Ok(poisson.sample_iter(rand::thread_rng()))
+    Ok(poisson.sample_iter(rng))
 }
 
-pub fn exponential(lambda: f64) -> Result<impl Iterator<Item = f64>, failure::Error> {
+pub fn exponential(
+    lambda: f64,
+    rng: impl Rng,
+) -> Result<impl Iterator<Item = f64>, failure::Error> {
     let exponential = Exponential::new(lambda)?;
-    Ok(exponential.sample_iter(rand::thread_rng()))
+    Ok(exponential.sample_iter(rng))
+}
+
+pub fn gamma(
+    shape: f64,
+    rate: f64,
+    rng: impl Rng,
+) -> Result<impl Iterator<Item = f64>, failure::Error> {
+    // statrs validates the parameters, but its gamma sampler is biased, so
+    // sampling uses rand_distr.
+    Gamma::new(shape, rate)?;
+    let gamma = rand_distr::Gamma::new(shape, 1.0 / rate).map_err(|e| format_err!("{:?}", e))?;
+    Ok(gamma.sample_iter(rng))
 }
 
-pub fn continuous_uniform(lower: f64, upper: f64) -> impl Iterator<Item = f64> {
+pub fn lognormal(
+    mean: f64,
+    variance: f64,
+    rng: impl Rng,
+) -> Result<impl Iterator<Item = f64>, failure::Error> {
+    LogNormal::new(mean, variance.sqrt())?;
+    let lognormal =
+        rand_distr::LogNormal::new(mean, variance.sqrt()).map_err(|e| format_err!("{:?}", e))?;
+    Ok(lognormal.sample_iter(rng))
+}
+
+pub fn continuous_uniform(lower: f64, upper: f64, rng: impl Rng) -> impl Iterator<Item = f64> {
     let uniform = Uniform::new(lower, upper);
-    uniform.sample_iter(rand::thread_rng())
+    uniform.sample_iter(rng)
 }
 
-pub fn discrete_uniform(lower: i64, upper: i64) -> impl Iterator<Item = i64> {
+pub fn discrete_uniform(lower: i64, upper: i64, rng: impl Rng) -> impl Iterator<Item = i64> {
     let uniform = Uniform::new_inclusive(lower, upper);
-    uniform.sample_iter(rand::thread_rng())
+    uniform.sample_iter(rng)
+}
+
+/// A distribution with fixed parameters, written like `normal(0, 1)`.
+///
+/// Parameters follow the sampling subcommands: normal(μ, σ²), exponential(λ),
+/// uniform(a, b), poisson(λ), binomial(n, p), gamma(k, β) and lognormal(μ, σ²).
+#[derive(Debug)]
+pub enum NamedDistribution {
+    Normal(Normal),
+    Exponential(Exponential),
+    Uniform(statrs::distribution::Uniform),
+    Poisson(Poisson),
+    Binomial(Binomial),
+    Gamma(Gamma),
+    // statrs does not expose the parameters of a log-normal distribution.
+    LogNormal {
+        mean: f64,
+        variance: f64,
+        distribution: LogNormal,
+    },
+}
+
+impl NamedDistribution {
+    pub fn is_discrete(&self) -> bool {
+        matches!(
+            self,
+            NamedDistribution::Poisson(_) | NamedDistribution::Binomial(_)
+        )
+    }
+
+    /// The probability of a value less than or equal to x.
+    pub fn cdf(&self, x: f64) -> f64 {
+        match self {
+            NamedDistribution::Normal(d) => d.cdf(x),
+            NamedDistribution::Exponential(d) => d.cdf(x),
+            NamedDistribution::Uniform(d) => d.cdf(x),
+            NamedDistribution::Poisson(d) => d.cdf(x),
+            NamedDistribution::Binomial(d) => d.cdf(x),
+            NamedDistribution::Gamma(d) => d.cdf(x),
+            NamedDistribution::LogNormal { distribution, .. } => distribution.cdf(x),
+        }
+    }
+
+    /// The log of the density (or, for discrete distributions, the
+    /// probability mass) at x.
+    pub fn ln_density(&self, x: f64) -> f64 {
+        let count = || {
+            if x >= 0.0 && x.fract() == 0.0 {
+                Some(x as u64)
+            } else {
+                None
+            }
+        };
+        match self {
+            NamedDistribution::Normal(d) => d.ln_pdf(x),
+            NamedDistribution::Exponential(d) => d.ln_pdf(x),
+            NamedDistribution::Uniform(d) => d.ln_pdf(x),
+            NamedDistribution::Poisson(d) => {
+                count().map_or(std::f64::NEG_INFINITY, |k| d.ln_pmf(k))
+            }
+            NamedDistribution::Binomial(d) => {
+                count().map_or(std::f64::NEG_INFINITY, |k| d.ln_pmf(k))
+            }
+            NamedDistribution::Gamma(d) => d.ln_pdf(x),
+            NamedDistribution::LogNormal { distribution, .. } => distribution.ln_pdf(x),
+        }
+    }
+
+    /// The number of free parameters of the distribution's family.
+    pub fn num_parameters(&self) -> usize {
+        match self {
+            NamedDistribution::Exponential(_) | NamedDistribution::Poisson(_) => 1,
+            _ => 2,
+        }
+    }
+
+    /// The subcommand that samples from this distribution.
+    pub fn command(&self) -> String {
+        match self {
+            NamedDistribution::Normal(d) => format!(
+                "samplers gaussian --mean {} --variance {}",
+                d.mean(),
+                d.variance()
+            ),
+            NamedDistribution::Exponential(d) => {
+                format!("samplers exponential --lambda {}", d.rate())
+            }
+            NamedDistribution::Uniform(d) => {
+                format!("samplers uniform --lower {} --upper {}", d.min(), d.max())
+            }
+            NamedDistribution::Poisson(d) => format!("samplers poisson --lambda {}", d.lambda()),
+            NamedDistribution::Binomial(d) => format!(
+                "samplers binomial --num-trials {} --probability {}",
+                d.n(),
+                d.p()
+            ),
+            NamedDistribution::Gamma(d) => {
+                format!("samplers gamma --shape {} --rate {}", d.shape(), d.rate())
+            }
+            NamedDistribution::LogNormal { mean, variance, .. } => {
+                format!("samplers lognormal --mean {} --variance {}", mean, variance)
+            }
+        }
+    }
+
+    /// The probability of a value strictly less than x. This differs from the
+    /// CDF only at the atoms of discrete distributions.
+    pub fn probability_below(&self, x: f64) -> f64 {
+        if self.is_discrete() {
+            self.cdf(x.ceil() - 1.0)
+        } else {
+            self.cdf(x)
+        }
+    }
+}
+
+impl std::str::FromStr for NamedDistribution {
+    type Err = failure::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || crate::SamplersError::InvalidArgument {
+            name: "distribution".to_string(),
+            value: s.to_string(),
+        };
+        let open = s.find('(').ok_or_else(invalid)?;
+        let close = s.rfind(')').ok_or_else(invalid)?;
+        if close < open || !s[close + 1..].trim().is_empty() {
+            return Err(invalid().into());
+        }
+        let name = s[..open].trim();
+        let parameters = s[open + 1..close]
+            .split(',')
+            .map(|parameter| parameter.trim().parse::<f64>())
+            .collect::<Result<Vec<f64>, _>>()
+            .map_err(|_| invalid())?;
+        Ok(match (name, parameters.as_slice()) {
+            ("normal", &[mean, variance]) | ("gaussian", &[mean, variance]) => {
+                NamedDistribution::Normal(Normal::new(mean, variance.sqrt())?)
+            }
+            ("exponential", &[lambda]) => NamedDistribution::Exponential(Exponential::new(lambda)?),
+            ("uniform", &[lower, upper]) => {
+                NamedDistribution::Uniform(statrs::distribution::Uniform::new(lower, upper)?)
+            }
+            ("poisson", &[lambda]) => NamedDistribution::Poisson(Poisson::new(lambda)?),
+            ("binomial", &[n, p]) if n >= 0.0 && n.fract() == 0.0 => {
+                NamedDistribution::Binomial(Binomial::new(p, n as u64)?)
+            }
+            ("gamma", &[shape, rate]) => NamedDistribution::Gamma(Gamma::new(shape, rate)?),
+            ("lognormal", &[mean, variance]) => NamedDistribution::LogNormal {
+                mean,
+                variance,
+                distribution: LogNormal::new(mean, variance.sqrt())?,
+            },
+            _ => return Err(invalid().into()),
+        })
+    }
+}
+
+impl fmt::Display for NamedDistribution {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            NamedDistribution::Normal(d) => write!(f, "normal({}, {})", d.mean(), d.variance()),
+            NamedDistribution::Exponential(d) => write!(f, "exponential({})", d.rate()),
+            NamedDistribution::Uniform(d) => write!(f, "uniform({}, {})", d.min(), d.max()),
+            NamedDistribution::Poisson(d) => write!(f, "poisson({})", d.lambda()),
+            NamedDistribution::Binomial(d) => write!(f, "binomial({}, {})", d.n(), d.p()),
+            NamedDistribution::Gamma(d) => write!(f, "gamma({}, {})", d.shape(), d.rate()),
+            NamedDistribution::LogNormal { mean, variance, .. } => {
+                write!(f, "lognormal({}, {})", mean, variance)
+            }
+        }
+    }
+}
+
+#[test]
+fn test_named_distribution() -> Result<(), failure::Error> {
+    let normal: NamedDistribution = "normal(1, 4)".parse()?;
+    assert_eq!(normal.to_string(), "normal(1, 4)");
+    assert_eq!(normal.cdf(1.0), 0.5);
+    assert!(!normal.is_discrete());
+
+    let poisson: NamedDistribution = " poisson( 2.5 ) ".trim().parse()?;
+    assert!(poisson.is_discrete());
+    assert_eq!(poisson.probability_below(0.0), 0.0);
+    assert_eq!(poisson.probability_below(1.0), poisson.cdf(0.0));
+    assert_eq!(poisson.probability_below(1.5), poisson.cdf(1.0));
+
+    let gamma: NamedDistribution = "gamma(2, 0.5)".parse()?;
+    assert_eq!(gamma.command(), "samplers gamma --shape 2 --rate 0.5");
+    assert_eq!(gamma.ln_density(-1.0), std::f64::NEG_INFINITY);
+    assert_eq!(poisson.ln_density(1.5), std::f64::NEG_INFINITY);
+
+    assert!("normal(0)".parse::<NamedDistribution>().is_err());
+    assert!("normal(0, -1)".parse::<NamedDistribution>().is_err());
+    assert!("cauchy(0, 1)".parse::<NamedDistribution>().is_err());
+    assert!("binomial(2.5, 0.5)".parse::<NamedDistribution>().is_err());
+    Ok(())
 }
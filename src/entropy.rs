@@ -0,0 +1,225 @@
+use std::collections::HashMap;
+
+use crate::histogram::Histogram;
+use crate::summary::Observer;
+use crate::SamplersError;
+
+/// How values are grouped into the outcomes of a discrete distribution.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Binning {
+    /// Each distinct value is an outcome.
+    Distinct,
+    /// Values fall into this many buckets of equal width between the smallest
+    /// and largest value, for continuous data.
+    Buckets(usize),
+}
+
+/// Parses the base of the logarithm, which is a number or `e`. Base 2 gives
+/// entropies in bits and base e in nats.
+pub fn parse_base(s: &str) -> Result<f64, failure::Error> {
+    let base = match s {
+        "e" => std::f64::consts::E,
+        _ => s.parse().unwrap_or(std::f64::NAN),
+    };
+    if base > 0.0 && base != 1.0 && base.is_finite() {
+        Ok(base)
+    } else {
+        Err(SamplersError::InvalidArgument {
+            name: "base".to_string(),
+            value: s.to_string(),
+        }
+        .into())
+    }
+}
+
+/// The total weight of each outcome in each sample of weighted values. The
+/// outcomes are the same for every sample, so that the weights can be
+/// compared position by position. NaN is not an outcome, and buckets need
+/// finite bounds, so either is an error.
+pub fn outcome_weights(
+    samples: &[&[(f64, f64)]],
+    binning: Binning,
+) -> Result<Vec<Vec<f64>>, failure::Error> {
+    let values = || samples.iter().flat_map(|sample| sample.iter());
+    if let Some(&(value, _weight)) = values().find(|(value, _weight)| value.is_nan()) {
+        return Err(SamplersError::CouldNotObserveValue { value }.into());
+    }
+    match binning {
+        Binning::Distinct => {
+            let mut outcomes = HashMap::new();
+            for (value, _weight) in values() {
+                // Adding zero folds -0 into 0.
+                let next = outcomes.len();
+                outcomes.entry((value + 0.0).to_bits()).or_insert(next);
+            }
+            Ok(samples
+                .iter()
+                .map(|sample| {
+                    let mut weights = vec![0.0; outcomes.len()];
+                    for (value, weight) in sample.iter() {
+                        weights[outcomes[&(value + 0.0).to_bits()]] += weight;
+                    }
+                    weights
+                })
+                .collect())
+        }
+        Binning::Buckets(0) => Err(SamplersError::InvalidArgument {
+            name: "bins".to_string(),
+            value: "0".to_string(),
+        }
+        .into()),
+        Binning::Buckets(num_buckets) => {
+            if let Some(&(value, _weight)) = values().find(|(value, _weight)| !value.is_finite()) {
+                return Err(SamplersError::CouldNotObserveValue { value }.into());
+            }
+            let (min, max) = values().fold(
+                (std::f64::INFINITY, std::f64::NEG_INFINITY),
+                |(min, max), &(value, _weight)| (min.min(value), max.max(value)),
+            );
+            samples
+                .iter()
+                .map(|sample| {
+                    let mut histogram = Histogram::with_bounds(min, max, num_buckets);
+                    for (value, weight) in sample.iter() {
+                        histogram.observe_weighted(value, *weight)?;
+                    }
+                    // Nothing falls below the smallest value, so the first
+                    // bucket is always empty and is left out. The largest
+                    // value falls in the bucket starting at it, which is
+                    // folded into the last of the requested buckets.
+                    let mut weights: Vec<f64> = histogram
+                        .collect()
+                        .iter()
+                        .skip(1)
+                        .map(|bucket| bucket.weight())
+                        .collect();
+                    let largest = weights.pop().unwrap_or_default();
+                    if let Some(last) = weights.last_mut() {
+                        *last += largest;
+                    }
+                    Ok(weights)
+                })
+                .collect()
+        }
+    }
+}
+
+/// Normalizes weights into probabilities that sum to 1.
+fn probabilities(weights: &[f64], name: &str) -> Result<Vec<f64>, failure::Error> {
+    let total: f64 = weights.iter().sum();
+    if total <= 0.0 || total.is_nan() {
+        return Err(SamplersError::CouldNotCalculateSummaryStatistic {
+            name: name.to_string(),
+        }
+        .into());
+    }
+    Ok(weights.iter().map(|weight| weight / total).collect())
+}
+
+/// The Shannon entropy -Σ p ln p of the distribution with the given weights
+/// for its outcomes, in units of the base.
+pub fn entropy(weights: &[f64], base: f64) -> Result<f64, failure::Error> {
+    let entropy: f64 = probabilities(weights, "entropy")?
+        .iter()
+        .filter(|&&p| p > 0.0)
+        .map(|p| -p * p.ln())
+        .sum();
+    // Adding zero turns the -0 of a single outcome into 0.
+    Ok(entropy / base.ln() + 0.0)
+}
+
+/// The Kullback–Leibler divergence Σ p ln(p / q) of the distribution P from
+/// the distribution Q, in units of the base. It is infinite if Q gives no
+/// weight to an outcome of P.
+pub fn kl_divergence(p: &[f64], q: &[f64], base: f64) -> Result<f64, failure::Error> {
+    let name = "KL divergence";
+    Ok(relative_entropy(&probabilities(p, name)?, &probabilities(q, name)?) / base.ln())
+}
+
+fn relative_entropy(p: &[f64], q: &[f64]) -> f64 {
+    p.iter()
+        .zip(q)
+        .filter(|&(&p, _)| p > 0.0)
+        .map(|(p, q)| p * (p / q).ln())
+        .sum()
+}
+
+/// The Jensen–Shannon distance between two distributions: the square root of
+/// the mean KL divergence of each from their mixture. Unlike the KL divergence
+/// it is symmetric and finite, and in base 2 it lies between 0 and 1.
+pub fn jensen_shannon_distance(p: &[f64], q: &[f64], base: f64) -> Result<f64, failure::Error> {
+    let name = "Jensen-Shannon distance";
+    let (p, q) = (probabilities(p, name)?, probabilities(q, name)?);
+    let mixture: Vec<f64> = p.iter().zip(&q).map(|(p, q)| (p + q) / 2.0).collect();
+    let divergence = (relative_entropy(&p, &mixture) + relative_entropy(&q, &mixture)) / 2.0;
+    // Rounding can leave a tiny negative divergence for equal distributions.
+    Ok((divergence.max(0.0) / base.ln()).sqrt())
+}
+
+#[test]
+fn test_entropy() -> Result<(), failure::Error> {
+    fn approx_eq(a: f64, b: f64) -> bool {
+        const THRESHOLD: f64 = 1e-12;
+        (a - b).abs() < THRESHOLD
+    }
+    let e = std::f64::consts::E;
+
+    assert_eq!(entropy(&[1.0, 1.0], 2.0)?, 1.0);
+    assert!(approx_eq(entropy(&[1.0, 1.0, 2.0], 2.0)?, 1.5));
+    assert_eq!(entropy(&[3.0, 0.0], 2.0)?, 0.0);
+    assert!(approx_eq(entropy(&[1.0; 10], 10.0)?, 1.0));
+    assert!(entropy(&[0.0, 0.0], 2.0).is_err());
+
+    // Matches scipy.stats.entropy and scipy.spatial.distance.jensenshannon.
+    let (p, q) = ([0.5, 0.5], [0.9, 0.1]);
+    assert!(approx_eq(kl_divergence(&p, &q, e)?, 0.5108256237659907));
+    assert!(approx_eq(
+        jensen_shannon_distance(&p, &q, e)?,
+        0.31898154347735663
+    ));
+    assert!(approx_eq(
+        jensen_shannon_distance(&q, &p, e)?,
+        0.31898154347735663
+    ));
+    assert_eq!(
+        kl_divergence(&[1.0, 1.0], &[1.0, 0.0], e)?,
+        std::f64::INFINITY
+    );
+    assert_eq!(kl_divergence(&[1.0, 0.0], &[1.0, 1.0], 2.0)?, 1.0);
+    assert_eq!(jensen_shannon_distance(&[1.0, 0.0], &[0.0, 1.0], 2.0)?, 1.0);
+    assert_eq!(jensen_shannon_distance(&p, &p, 2.0)?, 0.0);
+
+    let a = [(1.0, 1.0), (2.0, 1.0), (-0.0, 2.0)];
+    let b = [(3.0, 1.0), (0.0, 1.0), (2.0, 1.0)];
+    assert_eq!(
+        outcome_weights(&[&a, &b], Binning::Distinct)?,
+        vec![vec![1.0, 1.0, 2.0, 0.0], vec![0.0, 1.0, 1.0, 1.0]]
+    );
+    // The buckets span both samples, with the largest value in the last.
+    assert_eq!(
+        outcome_weights(&[&a, &b], Binning::Buckets(3))?,
+        vec![vec![2.0, 1.0, 1.0], vec![1.0, 0.0, 2.0]]
+    );
+    let constant = [(4.0, 1.0), (4.0, 2.0)];
+    assert_eq!(
+        outcome_weights(&[&constant], Binning::Buckets(2))?,
+        vec![vec![0.0, 3.0]]
+    );
+    let weights = outcome_weights(&[&constant], Binning::Distinct)?;
+    assert!(entropy(&weights[0], 2.0)?.is_sign_positive());
+    assert!(outcome_weights(&[&a], Binning::Buckets(0)).is_err());
+    let nan = [(1.0, 1.0), (std::f64::NAN, 1.0)];
+    assert!(outcome_weights(&[&a, &nan], Binning::Distinct).is_err());
+    assert!(outcome_weights(&[&nan], Binning::Buckets(2)).is_err());
+    let infinite = [(1.0, 1.0), (2.0, 1.0), (std::f64::INFINITY, 1.0)];
+    assert!(outcome_weights(&[&infinite], Binning::Buckets(3)).is_err());
+    assert!(outcome_weights(&[&a, &infinite], Binning::Buckets(3)).is_err());
+    assert_eq!(outcome_weights(&[&infinite], Binning::Distinct)?.len(), 1);
+
+    assert_eq!(parse_base("e")?, e);
+    assert_eq!(parse_base("10")?, 10.0);
+    for invalid in &["1", "0", "-2", "two", "inf"] {
+        assert!(parse_base(invalid).is_err());
+    }
+    Ok(())
+}
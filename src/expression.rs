@@ -0,0 +1,500 @@
+use crate::SamplersError;
+
+/// A function that can be called in an expression.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Function {
+    Abs,
+    Sqrt,
+    Exp,
+    Ln,
+    Log10,
+    Log2,
+    Sin,
+    Cos,
+    Tan,
+    Floor,
+    Ceil,
+    Round,
+    Pow,
+    Min,
+    Max,
+}
+
+impl Function {
+    fn from_name(name: &str) -> Option<Function> {
+        Some(match name {
+            "abs" => Function::Abs,
+            "sqrt" => Function::Sqrt,
+            "exp" => Function::Exp,
+            "ln" | "log" => Function::Ln,
+            "log10" => Function::Log10,
+            "log2" => Function::Log2,
+            "sin" => Function::Sin,
+            "cos" => Function::Cos,
+            "tan" => Function::Tan,
+            "floor" => Function::Floor,
+            "ceil" => Function::Ceil,
+            "round" => Function::Round,
+            "pow" => Function::Pow,
+            "min" => Function::Min,
+            "max" => Function::Max,
+            _ => return None,
+        })
+    }
+
+    /// Whether the function accepts the number of arguments.
+    fn accepts(&self, num_arguments: usize) -> bool {
+        match self {
+            Function::Pow => num_arguments == 2,
+            Function::Min | Function::Max => num_arguments >= 1,
+            _ => num_arguments == 1,
+        }
+    }
+
+    fn call(&self, arguments: &[f64]) -> f64 {
+        let x = arguments[0];
+        match self {
+            Function::Abs => x.abs(),
+            Function::Sqrt => x.sqrt(),
+            Function::Exp => x.exp(),
+            Function::Ln => x.ln(),
+            Function::Log10 => x.log10(),
+            Function::Log2 => x.log2(),
+            Function::Sin => x.sin(),
+            Function::Cos => x.cos(),
+            Function::Tan => x.tan(),
+            Function::Floor => x.floor(),
+            Function::Ceil => x.ceil(),
+            Function::Round => x.round(),
+            Function::Pow => x.powf(arguments[1]),
+            Function::Min => arguments.iter().cloned().fold(x, f64::min),
+            Function::Max => arguments.iter().cloned().fold(x, f64::max),
+        }
+    }
+}
+
+/// An operator with two operands. Comparisons and logical operators return 1
+/// for true and 0 for false.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BinaryOperator {
+    Or,
+    And,
+    Equal,
+    NotEqual,
+    Less,
+    LessOrEqual,
+    Greater,
+    GreaterOrEqual,
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+    Remainder,
+    Power,
+}
+
+impl BinaryOperator {
+    /// The operators that bind equally tightly, from the loosest level to the
+    /// tightest. Powers are handled separately, since they bind tighter than
+    /// negation and associate to the right.
+    const LEVELS: [&'static [(&'static str, BinaryOperator)]; 5] = [
+        &[("||", BinaryOperator::Or)],
+        &[("&&", BinaryOperator::And)],
+        &[
+            ("==", BinaryOperator::Equal),
+            ("!=", BinaryOperator::NotEqual),
+            ("<=", BinaryOperator::LessOrEqual),
+            (">=", BinaryOperator::GreaterOrEqual),
+            ("<", BinaryOperator::Less),
+            (">", BinaryOperator::Greater),
+        ],
+        &[("+", BinaryOperator::Add), ("-", BinaryOperator::Subtract)],
+        &[
+            ("*", BinaryOperator::Multiply),
+            ("/", BinaryOperator::Divide),
+            ("%", BinaryOperator::Remainder),
+        ],
+    ];
+
+    fn apply(&self, a: f64, b: f64) -> f64 {
+        let boolean = |condition: bool| if condition { 1.0 } else { 0.0 };
+        match self {
+            BinaryOperator::Or => boolean(is_true(a) || is_true(b)),
+            BinaryOperator::And => boolean(is_true(a) && is_true(b)),
+            BinaryOperator::Equal => boolean(a == b),
+            BinaryOperator::NotEqual => boolean(a != b),
+            BinaryOperator::Less => boolean(a < b),
+            BinaryOperator::LessOrEqual => boolean(a <= b),
+            BinaryOperator::Greater => boolean(a > b),
+            BinaryOperator::GreaterOrEqual => boolean(a >= b),
+            BinaryOperator::Add => a + b,
+            BinaryOperator::Subtract => a - b,
+            BinaryOperator::Multiply => a * b,
+            BinaryOperator::Divide => a / b,
+            BinaryOperator::Remainder => a % b,
+            BinaryOperator::Power => a.powf(b),
+        }
+    }
+}
+
+/// Whether a value counts as true in a condition: anything but 0 and NaN.
+pub fn is_true(value: f64) -> bool {
+    value != 0.0 && !value.is_nan()
+}
+
+/// An arithmetic expression over named variables, such as
+/// `max(x, 0) * 1000` or `latency > 0.5 && status == 200`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Expression {
+    Number(f64),
+    /// A variable, by its position in the expression's list of variables.
+    Variable(usize),
+    Negate(Box<Expression>),
+    Not(Box<Expression>),
+    Binary(BinaryOperator, Box<Expression>, Box<Expression>),
+    Call(Function, Vec<Expression>),
+}
+
+/// A parsed expression and the names of the variables it uses, in order of
+/// first use.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Formula {
+    pub expression: Expression,
+    pub variables: Vec<String>,
+}
+
+impl Formula {
+    /// Evaluates the expression with the values of its variables, in the
+    /// order of `variables`.
+    pub fn evaluate(&self, values: &[f64]) -> f64 {
+        evaluate(&self.expression, values)
+    }
+}
+
+fn evaluate(expression: &Expression, values: &[f64]) -> f64 {
+    match expression {
+        Expression::Number(number) => *number,
+        Expression::Variable(index) => values[*index],
+        Expression::Negate(operand) => -evaluate(operand, values),
+        Expression::Not(operand) => {
+            if is_true(evaluate(operand, values)) {
+                0.0
+            } else {
+                1.0
+            }
+        }
+        Expression::Binary(operator, a, b) => {
+            operator.apply(evaluate(a, values), evaluate(b, values))
+        }
+        Expression::Call(function, arguments) => {
+            let arguments: Vec<f64> = arguments.iter().map(|a| evaluate(a, values)).collect();
+            function.call(&arguments)
+        }
+    }
+}
+
+impl std::str::FromStr for Formula {
+    type Err = failure::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parser = Parser {
+            tokens: tokenize(s)?,
+            position: 0,
+            variables: Vec::new(),
+        };
+        let expression = parser.parse_binary(0)?;
+        match parser.tokens.get(parser.position) {
+            Some(token) => Err(invalid(format!("unexpected {}", token))),
+            None => Ok(Formula {
+                expression,
+                variables: parser.variables,
+            }),
+        }
+    }
+}
+
+fn invalid(reason: String) -> failure::Error {
+    SamplersError::InvalidArgument {
+        name: "expression".to_string(),
+        value: reason,
+    }
+    .into()
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Number(f64),
+    Name(String),
+    Operator(&'static str),
+    LeftParenthesis,
+    RightParenthesis,
+    Comma,
+}
+
+impl std::fmt::Display for Token {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Token::Number(number) => write!(f, "{}", number),
+            Token::Name(name) => write!(f, "{}", name),
+            Token::Operator(operator) => write!(f, "{}", operator),
+            Token::LeftParenthesis => write!(f, "("),
+            Token::RightParenthesis => write!(f, ")"),
+            Token::Comma => write!(f, ","),
+        }
+    }
+}
+
+/// The operators, with longer operators first so that `<=` is not read as
+/// `<` followed by `=`.
+const OPERATORS: [&str; 16] = [
+    "||", "&&", "==", "!=", "<=", ">=", "<", ">", "+", "-", "*", "/", "%", "^", "!", "=",
+];
+
+fn tokenize(s: &str) -> Result<Vec<Token>, failure::Error> {
+    let mut tokens = Vec::new();
+    let mut rest = s.trim_start();
+    while let Some(c) = rest.chars().next() {
+        let length = if c.is_ascii_digit() || c == '.' {
+            // A number, including an exponent such as 1e-3.
+            let mut end = 0;
+            let bytes = rest.as_bytes();
+            while end < bytes.len() {
+                let b = bytes[end];
+                let is_exponent_sign = (b == b'-' || b == b'+')
+                    && end > 0
+                    && (bytes[end - 1] == b'e' || bytes[end - 1] == b'E');
+                if !(b.is_ascii_digit() || b == b'.' || b == b'e' || b == b'E' || is_exponent_sign)
+                {
+                    break;
+                }
+                end += 1;
+            }
+            let number = rest[..end]
+                .parse()
+                .map_err(|_| invalid(format!("invalid number {}", &rest[..end])))?;
+            tokens.push(Token::Number(number));
+            end
+        } else if c.is_alphabetic() || c == '_' || c == '$' {
+            let end = rest
+                .char_indices()
+                .skip(1)
+                .find(|&(_, c)| !(c.is_alphanumeric() || c == '_'))
+                .map_or(rest.len(), |(i, _)| i);
+            tokens.push(Token::Name(rest[..end].to_string()));
+            end
+        } else if c == '(' {
+            tokens.push(Token::LeftParenthesis);
+            1
+        } else if c == ')' {
+            tokens.push(Token::RightParenthesis);
+            1
+        } else if c == ',' {
+            tokens.push(Token::Comma);
+            1
+        } else {
+            let operator = OPERATORS
+                .iter()
+                .find(|operator| rest.starts_with(*operator))
+                .ok_or_else(|| invalid(format!("unexpected {}", c)))?;
+            if *operator == "=" {
+                return Err(invalid(
+                    "= is not an operator; use == to compare".to_string(),
+                ));
+            }
+            tokens.push(Token::Operator(operator));
+            operator.len()
+        };
+        rest = rest[length..].trim_start();
+    }
+    Ok(tokens)
+}
+
+/// A recursive descent parser over the tokens of an expression.
+struct Parser {
+    tokens: Vec<Token>,
+    position: usize,
+    variables: Vec<String>,
+}
+
+impl Parser {
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.position).cloned();
+        self.position += 1;
+        token
+    }
+
+    fn peek_operator(&self) -> Option<&'static str> {
+        match self.tokens.get(self.position) {
+            Some(Token::Operator(operator)) => Some(operator),
+            _ => None,
+        }
+    }
+
+    fn expect(&mut self, expected: Token) -> Result<(), failure::Error> {
+        match self.next() {
+            Some(ref token) if *token == expected => Ok(()),
+            Some(token) => Err(invalid(format!(
+                "expected {} but found {}",
+                expected, token
+            ))),
+            None => Err(invalid(format!("expected {} at the end", expected))),
+        }
+    }
+
+    /// Parses operators at the given level of `BinaryOperator::LEVELS` and
+    /// tighter, which associate to the left.
+    fn parse_binary(&mut self, level: usize) -> Result<Expression, failure::Error> {
+        let operators = match BinaryOperator::LEVELS.get(level) {
+            Some(operators) => operators,
+            None => return self.parse_unary(),
+        };
+        let mut expression = self.parse_binary(level + 1)?;
+        while let Some(&(_, operator)) = self
+            .peek_operator()
+            .and_then(|token| operators.iter().find(|(symbol, _)| *symbol == token))
+        {
+            self.position += 1;
+            let right = self.parse_binary(level + 1)?;
+            expression = Expression::Binary(operator, Box::new(expression), Box::new(right));
+        }
+        Ok(expression)
+    }
+
+    fn parse_unary(&mut self) -> Result<Expression, failure::Error> {
+        match self.peek_operator() {
+            Some("-") => {
+                self.position += 1;
+                Ok(Expression::Negate(Box::new(self.parse_unary()?)))
+            }
+            Some("+") => {
+                self.position += 1;
+                self.parse_unary()
+            }
+            Some("!") => {
+                self.position += 1;
+                Ok(Expression::Not(Box::new(self.parse_unary()?)))
+            }
+            _ => self.parse_power(),
+        }
+    }
+
+    /// Parses a power, so that -x^2 is -(x^2) and 2^3^2 is 2^(3^2).
+    fn parse_power(&mut self) -> Result<Expression, failure::Error> {
+        let base = self.parse_primary()?;
+        if self.peek_operator() == Some("^") {
+            self.position += 1;
+            let exponent = self.parse_unary()?;
+            return Ok(Expression::Binary(
+                BinaryOperator::Power,
+                Box::new(base),
+                Box::new(exponent),
+            ));
+        }
+        Ok(base)
+    }
+
+    fn parse_primary(&mut self) -> Result<Expression, failure::Error> {
+        match self.next() {
+            Some(Token::Number(number)) => Ok(Expression::Number(number)),
+            Some(Token::LeftParenthesis) => {
+                let expression = self.parse_binary(0)?;
+                self.expect(Token::RightParenthesis)?;
+                Ok(expression)
+            }
+            Some(Token::Name(name)) => {
+                if self.tokens.get(self.position) == Some(&Token::LeftParenthesis) {
+                    self.position += 1;
+                    return self.parse_call(&name);
+                }
+                Ok(match name.as_str() {
+                    "pi" => Expression::Number(std::f64::consts::PI),
+                    "e" => Expression::Number(std::f64::consts::E),
+                    _ => Expression::Variable(self.variable(name)),
+                })
+            }
+            Some(token) => Err(invalid(format!("unexpected {}", token))),
+            None => Err(invalid("unexpected end".to_string())),
+        }
+    }
+
+    fn parse_call(&mut self, name: &str) -> Result<Expression, failure::Error> {
+        let function = Function::from_name(name)
+            .ok_or_else(|| invalid(format!("unknown function {}", name)))?;
+        let mut arguments = Vec::new();
+        if self.tokens.get(self.position) == Some(&Token::RightParenthesis) {
+            self.position += 1;
+        } else {
+            loop {
+                arguments.push(self.parse_binary(0)?);
+                match self.next() {
+                    Some(Token::Comma) => {}
+                    Some(Token::RightParenthesis) => break,
+                    _ => return Err(invalid(format!("unclosed call to {}", name))),
+                }
+            }
+        }
+        if !function.accepts(arguments.len()) {
+            return Err(invalid(format!(
+                "{} does not take {} arguments",
+                name,
+                arguments.len()
+            )));
+        }
+        Ok(Expression::Call(function, arguments))
+    }
+
+    /// The position of a variable, adding it on first use.
+    fn variable(&mut self, name: String) -> usize {
+        match self.variables.iter().position(|variable| *variable == name) {
+            Some(index) => index,
+            None => {
+                self.variables.push(name);
+                self.variables.len() - 1
+            }
+        }
+    }
+}
+
+#[test]
+fn test_expression() -> Result<(), failure::Error> {
+    let evaluate = |s: &str, values: &[f64]| -> Result<f64, failure::Error> {
+        Ok(s.parse::<Formula>()?.evaluate(values))
+    };
+
+    assert_eq!(evaluate("x * 1000 + 3", &[2.5])?, 2503.0);
+    assert_eq!(evaluate("1 + 2 * 3 - 4 / 2", &[])?, 5.0);
+    assert_eq!(evaluate("(1 + 2) * 3", &[])?, 9.0);
+    assert_eq!(evaluate("-2^2", &[])?, -4.0);
+    assert_eq!(evaluate("2^3^2", &[])?, 512.0);
+    assert_eq!(evaluate("2^-1", &[])?, 0.5);
+    assert_eq!(evaluate("7 % 4 - 1.5e1", &[])?, -12.0);
+    assert_eq!(evaluate("x > 0 && x < 10", &[5.0])?, 1.0);
+    assert_eq!(evaluate("x > 0 && x < 10", &[10.0])?, 0.0);
+    assert_eq!(evaluate("!(x <= 1) || x == -3", &[-3.0])?, 1.0);
+    assert_eq!(evaluate("min(exp(x), 30)", &[0.0])?, 1.0);
+    assert_eq!(evaluate("max(1, x, 3)", &[7.0])?, 7.0);
+    assert_eq!(evaluate("pow(2, 10) + round(2.5)", &[])?, 1027.0);
+    assert_eq!(evaluate("ln(e) + cos(pi)", &[])?, 0.0);
+
+    let formula: Formula = "latency * 1000 > $3 && latency < 2".parse()?;
+    assert_eq!(formula.variables, vec!["latency", "$3"]);
+    assert_eq!(formula.evaluate(&[0.5, 100.0]), 1.0);
+
+    assert!(is_true(-1.0));
+    assert!(!is_true(std::f64::NAN));
+    for invalid in &[
+        "",
+        "1 +",
+        "(1",
+        "1)",
+        "x = 1",
+        "foo(1)",
+        "sqrt(1, 2)",
+        "min()",
+        "2 3",
+        "1 # 2",
+    ] {
+        assert!(invalid.parse::<Formula>().is_err(), "{}", invalid);
+    }
+    Ok(())
+}
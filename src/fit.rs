@@ -0,0 +1,273 @@
+use std::fmt;
+
+use statrs::distribution::{Exponential, Gamma, LogNormal, Normal, Poisson};
+use statrs::function::gamma::digamma;
+
+use crate::distributions::NamedDistribution;
+use crate::summary::{self, DistributionSummary, Observer};
+use crate::SamplersError;
+
+/// A family of distributions whose parameters can be estimated.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Family {
+    Normal,
+    Exponential,
+    Uniform,
+    Poisson,
+    Gamma,
+    LogNormal,
+}
+
+impl Family {
+    /// The continuous families, which have comparable likelihoods.
+    pub const CONTINUOUS: [Family; 5] = [
+        Family::Normal,
+        Family::Exponential,
+        Family::Uniform,
+        Family::Gamma,
+        Family::LogNormal,
+    ];
+
+    /// The discrete families, whose likelihoods are probabilities rather than
+    /// densities and so are not comparable with those of continuous families.
+    pub const DISCRETE: [Family; 1] = [Family::Poisson];
+}
+
+impl std::str::FromStr for Family {
+    type Err = failure::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "normal" | "gaussian" => Ok(Family::Normal),
+            "exponential" => Ok(Family::Exponential),
+            "uniform" => Ok(Family::Uniform),
+            "poisson" => Ok(Family::Poisson),
+            "gamma" => Ok(Family::Gamma),
+            "lognormal" => Ok(Family::LogNormal),
+            _ => Err(SamplersError::InvalidArgument {
+                name: "distribution family".to_string(),
+                value: s.to_string(),
+            }
+            .into()),
+        }
+    }
+}
+
+impl fmt::Display for Family {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Family::Normal => "normal",
+            Family::Exponential => "exponential",
+            Family::Uniform => "uniform",
+            Family::Poisson => "poisson",
+            Family::Gamma => "gamma",
+            Family::LogNormal => "lognormal",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// An information criterion for comparing fits, where smaller is better.
+/// BIC penalizes parameters more than AIC once there are 8 or more values.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Criterion {
+    Aic,
+    Bic,
+}
+
+impl std::str::FromStr for Criterion {
+    type Err = failure::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "aic" => Ok(Criterion::Aic),
+            "bic" => Ok(Criterion::Bic),
+            _ => Err(SamplersError::InvalidArgument {
+                name: "criterion".to_string(),
+                value: s.to_string(),
+            }
+            .into()),
+        }
+    }
+}
+
+/// A distribution fitted by maximum likelihood.
+#[derive(Debug)]
+pub struct Fit {
+    distribution: NamedDistribution,
+    log_likelihood: f64,
+    aic: f64,
+    bic: f64,
+}
+
+impl Fit {
+    pub fn criterion(&self, criterion: Criterion) -> f64 {
+        match criterion {
+            Criterion::Aic => self.aic,
+            Criterion::Bic => self.bic,
+        }
+    }
+}
+
+/// Estimates the parameters of the family from the values by maximum
+/// likelihood.
+pub fn fit(family: Family, values: &[f64]) -> Result<Fit, failure::Error> {
+    let could_not_fit = || SamplersError::CouldNotFitDistribution {
+        family: family.to_string(),
+    };
+    let mut summary = DistributionSummary::default();
+    summary.observe_many(values.iter())?;
+    let mean = summary.mean().ok_or_else(could_not_fit)?;
+    let min = summary.min().ok_or_else(could_not_fit)?;
+    let max = summary.max().ok_or_else(could_not_fit)?;
+    let positive = || {
+        if min > 0.0 {
+            Ok(())
+        } else {
+            Err(could_not_fit())
+        }
+    };
+
+    let distribution = match family {
+        Family::Normal => NamedDistribution::Normal(Normal::new(
+            mean,
+            summary
+                .population_standard_deviation()
+                .ok_or_else(could_not_fit)?,
+        )?),
+        Family::Exponential => {
+            if min < 0.0 {
+                return Err(could_not_fit().into());
+            }
+            NamedDistribution::Exponential(Exponential::new(1.0 / mean)?)
+        }
+        Family::Uniform => {
+            NamedDistribution::Uniform(statrs::distribution::Uniform::new(min, max)?)
+        }
+        Family::Poisson => {
+            if values
+                .iter()
+                .any(|&value| value < 0.0 || value.fract() != 0.0)
+            {
+                return Err(could_not_fit().into());
+            }
+            NamedDistribution::Poisson(Poisson::new(mean)?)
+        }
+        Family::Gamma => {
+            positive()?;
+            let mean_log = summary::mean(values.iter().map(|value| value.ln()));
+            let shape = gamma_shape(mean.ln() - mean_log).ok_or_else(could_not_fit)?;
+            NamedDistribution::Gamma(Gamma::new(shape, shape / mean)?)
+        }
+        Family::LogNormal => {
+            positive()?;
+            let (variance, _sample_variance) = summary::variance(values.iter().map(|v| v.ln()));
+            let mean = summary::mean(values.iter().map(|value| value.ln()));
+            NamedDistribution::LogNormal {
+                mean,
+                variance,
+                distribution: LogNormal::new(mean, variance.sqrt())?,
+            }
+        }
+    };
+
+    let log_likelihood: f64 = values
+        .iter()
+        .map(|&value| distribution.ln_density(value))
+        .sum();
+    let k = distribution.num_parameters() as f64;
+    let n = values.len() as f64;
+    Ok(Fit {
+        aic: 2.0 * k - 2.0 * log_likelihood,
+        bic: k * n.ln() - 2.0 * log_likelihood,
+        distribution,
+        log_likelihood,
+    })
+}
+
+/// Solves ln(k) - ψ(k) = s for the maximum likelihood shape k of a gamma
+/// distribution, where s is the log of the mean minus the mean of the logs.
+/// The left side decreases monotonically in k, so this bisects in log space.
+fn gamma_shape(s: f64) -> Option<f64> {
+    if s <= 0.0 || !s.is_finite() {
+        return None;
+    }
+    let f = |k: f64| k.ln() - digamma(k) - s;
+    let (mut lower, mut upper) = (1e-8_f64, 1e8_f64);
+    if f(lower) < 0.0 || f(upper) > 0.0 {
+        return None;
+    }
+    for _ in 0..200 {
+        let middle = (lower.ln() + (upper.ln() - lower.ln()) / 2.0).exp();
+        if f(middle) > 0.0 {
+            lower = middle;
+        } else {
+            upper = middle;
+        }
+    }
+    Some((lower * upper).sqrt())
+}
+
+impl fmt::Display for Fit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Distribution: {}\nCommand: {}\nLog-likelihood: {}\nAIC: {}\nBIC: {}",
+            self.distribution,
+            self.distribution.command(),
+            self.log_likelihood,
+            self.aic,
+            self.bic,
+        )
+    }
+}
+
+#[test]
+fn test_fit() -> Result<(), failure::Error> {
+    use statrs::statistics::{Mean, Variance};
+
+    fn approx_eq(a: f64, b: f64) -> bool {
+        const THRESHOLD: f64 = 0.0001;
+        (a - b).abs() < THRESHOLD
+    }
+
+    let values = [0.5, 1.0, 1.5, 2.0, 4.0, 3.0];
+    let normal = fit(Family::Normal, &values)?;
+    match normal.distribution {
+        NamedDistribution::Normal(ref d) => {
+            assert!(approx_eq(d.mean(), 2.0));
+            assert!(approx_eq(d.variance(), 8.5 / 6.0));
+        }
+        _ => panic!("expected a normal distribution"),
+    }
+    // The maximized normal log-likelihood is -n/2 (ln(2πσ²) + 1).
+    assert!(approx_eq(
+        normal.log_likelihood,
+        -3.0 * ((2.0 * std::f64::consts::PI * 8.5 / 6.0).ln() + 1.0)
+    ));
+    assert!(approx_eq(normal.aic, 4.0 - 2.0 * normal.log_likelihood));
+    assert!(approx_eq(
+        normal.criterion(Criterion::Bic),
+        2.0 * 6f64.ln() - 2.0 * normal.log_likelihood
+    ));
+    assert_eq!("bic".parse::<Criterion>()?, Criterion::Bic);
+    assert!("dic".parse::<Criterion>().is_err());
+
+    let exponential = fit(Family::Exponential, &values)?;
+    assert_eq!(exponential.distribution.to_string(), "exponential(0.5)");
+
+    // Matches scipy.stats.gamma.fit(values, floc=0).
+    let gamma = fit(Family::Gamma, &values)?;
+    match gamma.distribution {
+        NamedDistribution::Gamma(ref d) => {
+            assert!(approx_eq(d.shape(), 2.519139));
+            assert!(approx_eq(d.rate(), 2.519139 / 2.0));
+        }
+        _ => panic!("expected a gamma distribution"),
+    }
+
+    assert!(fit(Family::Poisson, &values).is_err());
+    assert!(fit(Family::Gamma, &[-1.0, 2.0]).is_err());
+    assert!(fit(Family::Normal, &[]).is_err());
+    Ok(())
+}
@@ -0,0 +1,303 @@
+use std::fmt;
+
+use statrs::distribution::{ChiSquared, Univariate};
+
+use crate::distributions::NamedDistribution;
+use crate::histogram::Histogram;
+use crate::inference::TestResult;
+use crate::summary::Observer;
+
+// Adjacent histogram buckets are merged until each bin expects at least this
+// many values, the usual rule of thumb for the chi-squared approximation.
+const MIN_EXPECTED_COUNT: f64 = 5.0;
+
+/// Tests of whether values are drawn from a fully specified distribution.
+#[derive(Debug)]
+pub struct GoodnessOfFit<'a> {
+    distribution: &'a NamedDistribution,
+    count: usize,
+    kolmogorov_smirnov: Option<TestResult>,
+    anderson_darling: Option<TestResult>,
+    chi_squared: Option<ChiSquaredTestResult>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ChiSquaredTestResult {
+    pub test: TestResult,
+    pub degrees_of_freedom: f64,
+}
+
+impl<'a> GoodnessOfFit<'a> {
+    /// Runs every test. The Kolmogorov–Smirnov and Anderson–Darling tests
+    /// assume a continuous distribution and are skipped for discrete ones.
+    pub fn new(
+        values: &mut [f64],
+        distribution: &'a NamedDistribution,
+        num_buckets: usize,
+    ) -> Result<GoodnessOfFit<'a>, failure::Error> {
+        values.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+        let (kolmogorov_smirnov, anderson_darling) = if distribution.is_discrete() {
+            (None, None)
+        } else {
+            (
+                kolmogorov_smirnov_test(values, distribution),
+                anderson_darling_test(values, distribution),
+            )
+        };
+        let chi_squared = match (values.first(), values.last()) {
+            (Some(&min), Some(&max)) => {
+                let mut histogram = Histogram::with_bounds(min, max, num_buckets);
+                histogram.observe_many(values.iter())?;
+                chi_squared_test(&histogram, distribution)
+            }
+            _ => None,
+        };
+        Ok(GoodnessOfFit {
+            distribution,
+            count: values.len(),
+            kolmogorov_smirnov,
+            anderson_darling,
+            chi_squared,
+        })
+    }
+}
+
+/// The one-sample Kolmogorov–Smirnov test on sorted values, with p-values from
+/// the asymptotic Kolmogorov distribution using Stephens' correction for small
+/// samples.
+pub fn kolmogorov_smirnov_test(
+    sorted_values: &[f64],
+    distribution: &NamedDistribution,
+) -> Option<TestResult> {
+    if sorted_values.is_empty() {
+        return None;
+    }
+    let n = sorted_values.len() as f64;
+    let statistic = sorted_values
+        .iter()
+        .enumerate()
+        .map(|(i, &value)| {
+            let cdf = distribution.cdf(value);
+            (cdf - i as f64 / n).max((i + 1) as f64 / n - cdf)
+        })
+        .fold(0.0, f64::max);
+    let lambda = (n.sqrt() + 0.12 + 0.11 / n.sqrt()) * statistic;
+    Some(TestResult {
+        statistic,
+        p_value: kolmogorov_survival(lambda),
+    })
+}
+
+/// P(K > lambda) for the Kolmogorov distribution K.
+fn kolmogorov_survival(lambda: f64) -> f64 {
+    if lambda < 0.2 {
+        return 1.0;
+    }
+    let sum: f64 = (1..=100)
+        .map(|k| {
+            let k = f64::from(k);
+            let sign = if k as u64 % 2 == 1 { 1.0 } else { -1.0 };
+            sign * (-2.0 * k * k * lambda * lambda).exp()
+        })
+        .sum();
+    (2.0 * sum).clamp(0.0, 1.0)
+}
+
+/// The Anderson–Darling test on sorted values, with p-values from Marsaglia
+/// and Marsaglia, "Evaluating the Anderson-Darling Distribution" (2004).
+pub fn anderson_darling_test(
+    sorted_values: &[f64],
+    distribution: &NamedDistribution,
+) -> Option<TestResult> {
+    if sorted_values.is_empty() {
+        return None;
+    }
+    let n = sorted_values.len();
+    // Clamp the CDF so that values outside the support give a large but
+    // finite statistic.
+    let cdf = |value: f64| {
+        distribution
+            .cdf(value)
+            .clamp(std::f64::MIN_POSITIVE, 1.0 - std::f64::EPSILON)
+    };
+    let sum: f64 = (0..n)
+        .map(|i| {
+            (2 * i + 1) as f64
+                * (cdf(sorted_values[i]).ln() + (1.0 - cdf(sorted_values[n - 1 - i])).ln())
+        })
+        .sum();
+    let statistic = -(n as f64) - sum / n as f64;
+    Some(TestResult {
+        statistic,
+        p_value: 1.0 - anderson_darling_cdf(n as f64, statistic),
+    })
+}
+
+fn anderson_darling_cdf(n: f64, z: f64) -> f64 {
+    let asymptotic = if z <= 0.0 {
+        0.0
+    } else if z < 2.0 {
+        (-1.2337141 / z).exp() / z.sqrt()
+            * (2.00012
+                + (0.247105 - (0.0649821 - (0.0347962 - (0.011672 - 0.00168691 * z) * z) * z) * z)
+                    * z)
+    } else {
+        (-(1.0776
+            - (2.30695 - (0.43424 - (0.082433 - (0.008056 - 0.0003146 * z) * z) * z) * z) * z)
+            .exp())
+        .exp()
+    };
+    // Correct the asymptotic distribution for a finite sample size.
+    let x = asymptotic;
+    let correction = if x > 0.8 {
+        (-130.2137
+            + (745.2337 - (1705.091 - (1950.646 - (1116.360 - 255.7844 * x) * x) * x) * x) * x)
+            / n
+    } else {
+        let c = 0.01265 + 0.1757 / n;
+        if x < c {
+            let t = x / c;
+            let t = t.sqrt() * (1.0 - t) * (49.0 * t - 102.0);
+            t * (0.0037 / (n * n) + 0.00078 / n + 0.00006) / n
+        } else {
+            let t = (x - c) / (0.8 - c);
+            let t = -0.00022633
+                + (6.54034 - (14.6538 - (14.458 - (8.259 - 1.91864 * t) * t) * t) * t) * t;
+            t * (0.04213 / n + 0.01365 / (n * n))
+        }
+    };
+    (asymptotic + correction).clamp(0.0, 1.0)
+}
+
+/// Pearson's chi-squared test comparing the counts in the histogram's buckets
+/// to the counts expected under the distribution.
+pub fn chi_squared_test(
+    histogram: &Histogram,
+    distribution: &NamedDistribution,
+) -> Option<ChiSquaredTestResult> {
+    let buckets = histogram.collect();
+    let total: u64 = buckets.iter().map(|bucket| bucket.count()).sum();
+    let n = total as f64;
+
+    let mut bins: Vec<(f64, f64)> = Vec::new();
+    let (mut observed, mut expected) = (0.0, 0.0);
+    for bucket in &buckets {
+        observed += bucket.count() as f64;
+        expected += n
+            * (distribution.probability_below(bucket.upper())
+                - distribution.probability_below(bucket.lower()));
+        if expected >= MIN_EXPECTED_COUNT {
+            bins.push((observed, expected));
+            observed = 0.0;
+            expected = 0.0;
+        }
+    }
+    // Fold any remainder into the last bin.
+    match bins.last_mut() {
+        Some(last) => {
+            last.0 += observed;
+            last.1 += expected;
+        }
+        None => bins.push((observed, expected)),
+    }
+
+    let degrees_of_freedom = bins.len() as f64 - 1.0;
+    if degrees_of_freedom < 1.0 {
+        return None;
+    }
+    let statistic: f64 = bins
+        .iter()
+        .map(|&(observed, expected)| (observed - expected).powi(2) / expected)
+        .sum();
+    let chi_squared = ChiSquared::new(degrees_of_freedom).ok()?;
+    Some(ChiSquaredTestResult {
+        test: TestResult {
+            statistic,
+            p_value: 1.0 - chi_squared.cdf(statistic),
+        },
+        degrees_of_freedom,
+    })
+}
+
+impl fmt::Display for GoodnessOfFit<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let not_applicable = if self.distribution.is_discrete() {
+            "not applicable to discrete distributions"
+        } else {
+            "NaN"
+        };
+        writeln!(f, "Distribution: {}", self.distribution)?;
+        writeln!(f, "Count: {}", self.count)?;
+        match self.kolmogorov_smirnov {
+            Some(test) => writeln!(
+                f,
+                "Kolmogorov-Smirnov: D = {}, p = {}",
+                test.statistic, test.p_value
+            )?,
+            None => writeln!(f, "Kolmogorov-Smirnov: {}", not_applicable)?,
+        }
+        match self.anderson_darling {
+            Some(test) => writeln!(
+                f,
+                "Anderson-Darling: A² = {}, p = {}",
+                test.statistic, test.p_value
+            )?,
+            None => writeln!(f, "Anderson-Darling: {}", not_applicable)?,
+        }
+        match self.chi_squared {
+            Some(chi_squared) => write!(
+                f,
+                "Chi-squared: X² = {}, df = {}, p = {}",
+                chi_squared.test.statistic,
+                chi_squared.degrees_of_freedom,
+                chi_squared.test.p_value
+            ),
+            None => write!(f, "Chi-squared: NaN"),
+        }
+    }
+}
+
+#[test]
+fn test_goodness_of_fit() -> Result<(), failure::Error> {
+    fn approx_eq(a: f64, b: f64) -> bool {
+        const THRESHOLD: f64 = 0.0001;
+        (a - b).abs() < THRESHOLD
+    }
+
+    let normal: NamedDistribution = "normal(0, 1)".parse()?;
+    let mut values = [-1.2, -0.8, -0.3, 0.1, 0.4, 0.6, 1.1, 1.9];
+    let fit = GoodnessOfFit::new(&mut values, &normal, 3)?;
+    let kolmogorov_smirnov = fit.kolmogorov_smirnov.unwrap();
+    assert!(approx_eq(kolmogorov_smirnov.statistic, 0.164828));
+    assert!(kolmogorov_smirnov.p_value > 0.9);
+    let anderson_darling = fit.anderson_darling.unwrap();
+    assert!(approx_eq(anderson_darling.statistic, 0.285460));
+    assert!(anderson_darling.p_value > 0.9);
+
+    // Values far from the distribution are rejected by every test.
+    let mut values: Vec<f64> = (0..100).map(|i| 1.0 + f64::from(i) / 100.0).collect();
+    let fit = GoodnessOfFit::new(&mut values, &normal, 15)?;
+    assert!(fit.kolmogorov_smirnov.unwrap().p_value < 1e-6);
+    assert!(fit.anderson_darling.unwrap().p_value < 1e-3);
+    assert!(fit.chi_squared.unwrap().test.p_value < 1e-6);
+
+    let poisson: NamedDistribution = "poisson(2)".parse()?;
+    let mut values = [0.0, 1.0, 1.0, 2.0, 2.0, 2.0, 3.0, 3.0, 4.0, 5.0];
+    let fit = GoodnessOfFit::new(&mut values, &poisson, 5)?;
+    assert_eq!(fit.kolmogorov_smirnov, None);
+    assert_eq!(fit.anderson_darling, None);
+    Ok(())
+}
+
+#[test]
+fn test_anderson_darling_cdf() {
+    fn approx_eq(a: f64, b: f64) -> bool {
+        const THRESHOLD: f64 = 0.001;
+        (a - b).abs() < THRESHOLD
+    }
+
+    // Asymptotic percentage points of the Anderson–Darling statistic.
+    assert!(approx_eq(anderson_darling_cdf(1000.0, 1.933), 0.90));
+    assert!(approx_eq(anderson_darling_cdf(1000.0, 2.492), 0.95));
+    assert!(approx_eq(anderson_darling_cdf(1000.0, 3.857), 0.99));
+}
@@ -1,4 +1,4 @@
-use crate::summary::Observer;
+use crate::summary::{self, Observer};
 use crate::SamplersError;
 
 fn linspace(min: f64, max: f64, num: usize) -> Vec<f64> {
@@ -22,6 +22,8 @@ fn test_linspace() {
 pub struct Histogram {
     boundaries: Vec<f64>,
     counts: Vec<u64>,
+    // The sum of the weights of the values in each bucket.
+    weights: Vec<f64>,
 }
 
 impl Histogram {
@@ -33,6 +35,7 @@ impl Histogram {
         // TODO: validate boundaries
         Histogram {
             counts: vec![0; boundaries.len() + 1],
+            weights: vec![0.0; boundaries.len() + 1],
             boundaries,
         }
     }
@@ -44,33 +47,34 @@ impl Histogram {
             .chain(self.boundaries.iter().cloned())
             .chain(std::iter::once(std::f64::INFINITY))
             .tuple_windows::<(f64, f64)>()
-            .zip(self.counts.iter())
-            .map(|((lower, upper), &count)| Bucket {
+            .zip(self.counts.iter().zip(self.weights.iter()))
+            .map(|((lower, upper), (&count, &weight))| Bucket {
                 lower,
                 upper,
                 count,
+                weight,
             })
             .collect()
     }
 }
 
 impl Observer<'_, f64> for Histogram {
-    fn observe(&mut self, &value: &f64) -> Result<(), failure::Error> {
-        let mut it = self
+    fn observe_weighted(&mut self, &value: &f64, weight: f64) -> Result<(), failure::Error> {
+        summary::validate_weight(weight)?;
+        if weight == 0.0 {
+            return Ok(());
+        }
+        let index = self
             .boundaries
             .iter()
-            .enumerate()
-            .filter(|(_index, &boundary)| value < boundary);
-        if let Some((index, _boundary)) = it.next() {
-            if let Some(count) = self.counts.get_mut(index) {
+            .position(|&boundary| value < boundary)
+            .unwrap_or(self.boundaries.len());
+        match (self.counts.get_mut(index), self.weights.get_mut(index)) {
+            (Some(count), Some(total)) => {
                 *count += 1;
-            } else {
-                return Err(SamplersError::CouldNotObserveValue { value }.into());
+                *total += weight;
             }
-        } else if let Some(last) = self.counts.last_mut() {
-            *last += 1;
-        } else {
-            return Err(SamplersError::CouldNotObserveValue { value }.into());
+            _ => return Err(SamplersError::CouldNotObserveValue { value }.into()),
         }
         Ok(())
     }
@@ -96,6 +100,15 @@ fn test_histogram() -> Result<(), failure::Error> {
     histogram.observe_many([-20.0, 120.0, 2.0].iter())?;
     assert_eq!(histogram.counts, vec![1, 2]);
 
+    let mut histogram = Histogram::with_boundaries(vec![0.0]);
+    histogram.observe_weighted(&-1.0, 0.5)?;
+    histogram.observe_weighted(&1.0, 2.0)?;
+    histogram.observe_weighted(&2.0, 0.0)?;
+    histogram.observe(&3.0)?;
+    assert_eq!(histogram.counts, vec![1, 2]);
+    assert_eq!(histogram.weights, vec![0.5, 3.0]);
+    assert!(histogram.observe_weighted(&1.0, -1.0).is_err());
+
     Ok(())
 }
 
@@ -104,6 +117,7 @@ pub struct Bucket {
     lower: f64,
     upper: f64,
     count: u64,
+    weight: f64,
 }
 
 impl Bucket {
@@ -118,4 +132,10 @@ impl Bucket {
     pub fn count(&self) -> u64 {
         self.count
     }
+
+    /// The sum of the weights of the values in the bucket, which is the count
+    /// if no weights were given.
+    pub fn weight(&self) -> f64 {
+        self.weight
+    }
 }
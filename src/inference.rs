@@ -0,0 +1,208 @@
+use std::fmt;
+
+use statrs::distribution::{ChiSquared, InverseCDF, Normal, StudentsT, Univariate};
+
+use crate::SamplersError;
+
+/// A two-sided interval estimate `[lower, upper]`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ConfidenceInterval {
+    pub lower: f64,
+    pub upper: f64,
+}
+
+impl fmt::Display for ConfidenceInterval {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "[{}, {}]", self.lower, self.upper)
+    }
+}
+
+pub fn validate_confidence_level(level: f64) -> Result<f64, failure::Error> {
+    if level > 0.0 && level < 1.0 {
+        Ok(level)
+    } else {
+        Err(SamplersError::InvalidArgument {
+            name: "confidence level".to_string(),
+            value: level.to_string(),
+        }
+        .into())
+    }
+}
+
+/// The outcome of a hypothesis test: its test statistic and the probability of
+/// a statistic at least as extreme under the null hypothesis.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TestResult {
+    pub statistic: f64,
+    pub p_value: f64,
+}
+
+impl fmt::Display for TestResult {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "statistic = {}, p = {}", self.statistic, self.p_value)
+    }
+}
+
+/// The two-sided p-value of a standard normal test statistic.
+pub fn normal_two_sided_p_value(z: f64) -> f64 {
+    statrs::function::erf::erfc(z.abs() / std::f64::consts::SQRT_2)
+}
+
+/// The probability that a standard normal variable exceeds z.
+pub fn normal_upper_tail_p_value(z: f64) -> f64 {
+    statrs::function::erf::erfc(z / std::f64::consts::SQRT_2) / 2.0
+}
+
+/// The two-sided p-value of a Student's t test statistic.
+pub fn students_t_two_sided_p_value(t: f64, degrees_of_freedom: f64) -> Option<f64> {
+    let students_t = StudentsT::new(0.0, 1.0, degrees_of_freedom).ok()?;
+    Some(2.0 * (1.0 - students_t.cdf(t.abs())))
+}
+
+/// Finds x such that `cdf(x) = p` by bracketing and bisection.
+///
+/// statrs only provides inverse CDFs for a handful of distributions, so this
+/// is used for the others. The search starts from `[lower, upper]` and
+/// widens the bracket as necessary.
+pub fn inverse_cdf(cdf: impl Fn(f64) -> f64, p: f64, mut lower: f64, mut upper: f64) -> f64 {
+    while cdf(lower) > p {
+        let width = upper - lower;
+        lower -= width;
+    }
+    while cdf(upper) < p {
+        let width = upper - lower;
+        upper += width;
+    }
+    for _ in 0..200 {
+        let middle = lower + (upper - lower) / 2.0;
+        if middle <= lower || middle >= upper {
+            break;
+        }
+        if cdf(middle) < p {
+            lower = middle;
+        } else {
+            upper = middle;
+        }
+    }
+    lower + (upper - lower) / 2.0
+}
+
+/// The standard normal quantile function.
+pub fn normal_quantile(p: f64) -> f64 {
+    Normal::new(0.0, 1.0)
+        .expect("standard normal distribution is valid")
+        .inverse_cdf(p)
+}
+
+/// The quantile function of Student's t distribution.
+pub fn students_t_quantile(p: f64, degrees_of_freedom: f64) -> Option<f64> {
+    let students_t = StudentsT::new(0.0, 1.0, degrees_of_freedom).ok()?;
+    Some(inverse_cdf(|x| students_t.cdf(x), p, -1.0, 1.0))
+}
+
+/// The quantile function of the chi-squared distribution.
+pub fn chi_squared_quantile(p: f64, degrees_of_freedom: f64) -> Option<f64> {
+    let chi_squared = ChiSquared::new(degrees_of_freedom).ok()?;
+    Some(inverse_cdf(
+        |x| chi_squared.cdf(x),
+        p,
+        0.0,
+        degrees_of_freedom.max(1.0),
+    ))
+}
+
+/// A t-based interval for the mean of a normal population, given the sample
+/// size, sample mean and sample variance. The sample size may be an effective
+/// sample size for weighted observations.
+pub fn mean_interval(n: f64, mean: f64, variance: f64, level: f64) -> Option<ConfidenceInterval> {
+    if n < 2.0 {
+        return None;
+    }
+    let standard_error = (variance / n).sqrt();
+    let t = students_t_quantile(1.0 - (1.0 - level) / 2.0, n - 1.0)?;
+    Some(ConfidenceInterval {
+        lower: mean - t * standard_error,
+        upper: mean + t * standard_error,
+    })
+}
+
+/// A chi-squared interval for the variance of a normal population, given the
+/// sample size and sample variance.
+pub fn variance_interval(n: f64, variance: f64, level: f64) -> Option<ConfidenceInterval> {
+    if n < 2.0 {
+        return None;
+    }
+    let degrees_of_freedom = n - 1.0;
+    let alpha = 1.0 - level;
+    let moment2 = degrees_of_freedom * variance;
+    Some(ConfidenceInterval {
+        lower: moment2 / chi_squared_quantile(1.0 - alpha / 2.0, degrees_of_freedom)?,
+        upper: moment2 / chi_squared_quantile(alpha / 2.0, degrees_of_freedom)?,
+    })
+}
+
+/// The Wilson score interval for a binomial proportion, given the number of
+/// trials and the observed proportion of successes.
+pub fn wilson_interval(n: f64, proportion: f64, level: f64) -> Option<ConfidenceInterval> {
+    if n <= 0.0 {
+        return None;
+    }
+    let z = normal_quantile(1.0 - (1.0 - level) / 2.0);
+    let z2 = z * z;
+    let denominator = 1.0 + z2 / n;
+    let center = (proportion + z2 / (2.0 * n)) / denominator;
+    let half_width =
+        z / denominator * (proportion * (1.0 - proportion) / n + z2 / (4.0 * n * n)).sqrt();
+    Some(ConfidenceInterval {
+        lower: center - half_width,
+        upper: center + half_width,
+    })
+}
+
+#[test]
+fn test_quantiles() {
+    fn approx_eq(a: f64, b: f64) -> bool {
+        const THRESHOLD: f64 = 0.0001;
+        (a - b).abs() < THRESHOLD
+    }
+
+    assert!(approx_eq(normal_quantile(0.975), 1.959964));
+    assert!(approx_eq(
+        students_t_quantile(0.975, 5.0).unwrap(),
+        2.570582
+    ));
+    assert!(approx_eq(
+        students_t_quantile(0.05, 10.0).unwrap(),
+        -1.812461
+    ));
+    assert!(approx_eq(
+        chi_squared_quantile(0.025, 5.0).unwrap(),
+        0.8312116
+    ));
+    assert!(approx_eq(
+        chi_squared_quantile(0.975, 5.0).unwrap(),
+        12.83250
+    ));
+}
+
+#[test]
+fn test_intervals() {
+    fn approx_eq(a: f64, b: f64) -> bool {
+        const THRESHOLD: f64 = 0.0001;
+        (a - b).abs() < THRESHOLD
+    }
+
+    // The values [-1.25, 6.25, 16.0, -6.25, 1.25, 8.0].
+    let interval = mean_interval(6.0, 4.0, 61.05, 0.95).unwrap();
+    assert!(approx_eq(interval.lower, -4.199713));
+    assert!(approx_eq(interval.upper, 12.199713));
+    let interval = variance_interval(6.0, 61.05, 0.95).unwrap();
+    assert!(approx_eq(interval.lower, 23.787253));
+    assert!(approx_eq(interval.upper, 367.235004));
+    assert_eq!(mean_interval(1.0, 4.0, 0.0, 0.95), None);
+
+    // 7 successes out of 20 trials.
+    let interval = wilson_interval(20.0, 0.35, 0.95).unwrap();
+    assert!(approx_eq(interval.lower, 0.181192));
+    assert!(approx_eq(interval.upper, 0.567146));
+}
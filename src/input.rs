@@ -0,0 +1,913 @@
+use std::io::BufRead;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+use clap::ArgMatches;
+
+use crate::summary::Weights;
+use crate::SamplersError;
+
+/// A column of input, selected by its 1-based index or by its name in the
+/// header.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Column {
+    Index(usize),
+    Name(String),
+}
+
+impl std::str::FromStr for Column {
+    type Err = failure::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.parse::<usize>() {
+            Ok(0) => Err(SamplersError::InvalidArgument {
+                name: "column".to_string(),
+                value: s.to_string(),
+            }
+            .into()),
+            Ok(index) => Ok(Column::Index(index)),
+            Err(_) => Ok(Column::Name(s.to_string())),
+        }
+    }
+}
+
+/// How often to report while reading input: after a number of lines, or after
+/// a duration with a unit of ms, s, m or h, such as `10s`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Interval {
+    Lines(u64),
+    Duration(std::time::Duration),
+}
+
+impl std::str::FromStr for Interval {
+    type Err = failure::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || SamplersError::InvalidArgument {
+            name: "interval".to_string(),
+            value: s.to_string(),
+        };
+        if let Ok(lines) = s.parse::<u64>() {
+            return match lines {
+                0 => Err(invalid().into()),
+                _ => Ok(Interval::Lines(lines)),
+            };
+        }
+        let unit_start = s
+            .find(|c: char| c.is_ascii_alphabetic())
+            .ok_or_else(invalid)?;
+        let amount: f64 = s[..unit_start].parse().map_err(|_| invalid())?;
+        let seconds = match &s[unit_start..] {
+            "ms" => amount / 1000.0,
+            "s" => amount,
+            "m" => amount * 60.0,
+            "h" => amount * 3600.0,
+            _ => return Err(invalid().into()),
+        };
+        if !(seconds > 0.0 && seconds.is_finite()) {
+            return Err(invalid().into());
+        }
+        Ok(Interval::Duration(std::time::Duration::from_secs_f64(
+            seconds,
+        )))
+    }
+}
+
+#[test]
+fn test_interval() {
+    use std::time::Duration;
+
+    assert_eq!("100".parse::<Interval>().unwrap(), Interval::Lines(100));
+    assert_eq!(
+        "10s".parse::<Interval>().unwrap(),
+        Interval::Duration(Duration::from_secs(10))
+    );
+    assert_eq!(
+        "250ms".parse::<Interval>().unwrap(),
+        Interval::Duration(Duration::from_millis(250))
+    );
+    assert_eq!(
+        "1.5m".parse::<Interval>().unwrap(),
+        Interval::Duration(Duration::from_secs(90))
+    );
+    assert!("0".parse::<Interval>().is_err());
+    assert!("0s".parse::<Interval>().is_err());
+    assert!("10d".parse::<Interval>().is_err());
+    assert!("s".parse::<Interval>().is_err());
+}
+
+/// What to do with a line of input that cannot be parsed.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum OnInvalid {
+    /// Stop reading with an error naming the line.
+    Error,
+    /// Skip the line.
+    Skip,
+    /// Skip the line after printing a warning naming it.
+    Warn,
+}
+
+impl Default for OnInvalid {
+    fn default() -> Self {
+        OnInvalid::Error
+    }
+}
+
+impl std::str::FromStr for OnInvalid {
+    type Err = failure::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "error" => Ok(OnInvalid::Error),
+            "skip" => Ok(OnInvalid::Skip),
+            "warn" => Ok(OnInvalid::Warn),
+            _ => Err(SamplersError::InvalidArgument {
+                name: "invalid line policy".to_string(),
+                value: s.to_string(),
+            }
+            .into()),
+        }
+    }
+}
+
+/// What to do with a line holding a value that is not a number, such as
+/// `NaN`, which would otherwise make every moment NaN.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum OnNan {
+    Skip,
+    Propagate,
+}
+
+impl Default for OnNan {
+    fn default() -> Self {
+        OnNan::Propagate
+    }
+}
+
+impl std::str::FromStr for OnNan {
+    type Err = failure::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "skip" => Ok(OnNan::Skip),
+            "propagate" => Ok(OnNan::Propagate),
+            _ => Err(SamplersError::InvalidArgument {
+                name: "NaN policy".to_string(),
+                value: s.to_string(),
+            }
+            .into()),
+        }
+    }
+}
+
+/// The number of lines skipped while reading input, by reason.
+///
+/// The counts are shared by every copy of the policy that read the input,
+/// including those moved to other threads.
+#[derive(Debug, Default)]
+pub struct SkippedLines {
+    invalid: AtomicU64,
+    nan: AtomicU64,
+}
+
+impl SkippedLines {
+    pub fn invalid(&self) -> u64 {
+        self.invalid.load(Ordering::Relaxed)
+    }
+
+    pub fn nan(&self) -> u64 {
+        self.nan.load(Ordering::Relaxed)
+    }
+
+    /// A description of the skipped lines, if there were any.
+    pub fn report(&self) -> Option<String> {
+        let (invalid, nan) = (self.invalid(), self.nan());
+        let reasons: Vec<String> = [(invalid, "invalid"), (nan, "NaN")]
+            .iter()
+            .filter(|(count, _reason)| *count > 0)
+            .map(|(count, reason)| format!("{} {}", count, reason))
+            .collect();
+        if reasons.is_empty() {
+            return None;
+        }
+        let total = invalid + nan;
+        Some(format!(
+            "Skipped {} line{}: {}",
+            total,
+            if total == 1 { "" } else { "s" },
+            reasons.join(", ")
+        ))
+    }
+}
+
+/// How lines that cannot be parsed, and values that are NaN, are handled.
+#[derive(Debug, Default, Clone)]
+pub struct InputPolicy {
+    pub on_invalid: OnInvalid,
+    pub on_nan: OnNan,
+    skipped: Arc<SkippedLines>,
+}
+
+impl InputPolicy {
+    /// Applies the policy to the result of parsing a line, returning `None`
+    /// if the line is skipped. The location describes the line in errors
+    /// and warnings, such as `line 3`.
+    pub fn screen<T>(
+        &self,
+        parsed: Result<T, failure::Error>,
+        location: impl Fn() -> String,
+        is_nan: impl Fn(&T) -> bool,
+    ) -> Option<Result<T, failure::Error>> {
+        match parsed {
+            Ok(parsed) if self.on_nan == OnNan::Skip && is_nan(&parsed) => {
+                self.skipped.nan.fetch_add(1, Ordering::Relaxed);
+                None
+            }
+            Ok(parsed) => Some(Ok(parsed)),
+            Err(error) => {
+                let error = SamplersError::InvalidInput {
+                    location: location(),
+                    reason: error.to_string(),
+                };
+                match self.on_invalid {
+                    OnInvalid::Error => return Some(Err(error.into())),
+                    OnInvalid::Warn => eprintln!("Warning: {}", error),
+                    OnInvalid::Skip => {}
+                }
+                self.skipped.invalid.fetch_add(1, Ordering::Relaxed);
+                None
+            }
+        }
+    }
+}
+
+/// The value of every field of a line of input, which is `None` for an empty
+/// field.
+pub type Row = Result<Vec<Option<f64>>, failure::Error>;
+
+/// A line of input and the value parsed from it.
+pub type LineWithValue = Result<(String, f64), failure::Error>;
+
+/// The values in the selected columns of a line of input.
+pub type Values = Result<Vec<f64>, failure::Error>;
+
+/// A line of input and the values in its selected columns.
+pub type LineWithValues = Result<(String, Vec<f64>), failure::Error>;
+
+/// A line of input and its 1-based line number.
+type NumberedLine = (u64, std::io::Result<String>);
+
+/// How values, and optionally their weights, are read from lines of input.
+///
+/// Without a delimiter, fields are separated by whitespace. By default each
+/// line holds a single value, or a value followed by its weight when weights
+/// are given. Selecting columns allows any number of other fields.
+#[derive(Debug, Clone, Default)]
+pub struct InputFormat {
+    delimiter: Option<char>,
+    header: bool,
+    column: Option<Column>,
+    weight_column: Option<Column>,
+    weights: Option<Weights>,
+    group_by: Option<Column>,
+    columns: Vec<Column>,
+    policy: InputPolicy,
+}
+
+impl InputFormat {
+    /// Reads the input options shared by every subcommand that reads values.
+    /// Options that a subcommand does not define are left unset.
+    pub fn from_matches(matches: &ArgMatches) -> Result<InputFormat, failure::Error> {
+        let delimiter = match matches.value_of("delimiter") {
+            Some(delimiter) => Some(parse_delimiter(delimiter)?),
+            None => None,
+        };
+        let column = match matches.value_of("column") {
+            Some(column) => Some(column.parse()?),
+            None => None,
+        };
+        let weight_column = match matches.value_of("weight-column") {
+            Some(column) => Some(column.parse()?),
+            None => None,
+        };
+        let group_by = match matches.value_of("group-by") {
+            Some(column) => Some(column.parse()?),
+            None => None,
+        };
+        let weights = match matches.value_of("weights") {
+            Some(weights) => Some(weights.parse()?),
+            None if weight_column.is_some() => Some(Weights::default()),
+            None => None,
+        };
+        let columns = matches
+            .values_of("columns")
+            .map(|columns| columns.map(str::parse).collect())
+            .transpose()?
+            .unwrap_or_default();
+        let policy = InputPolicy {
+            on_invalid: match matches.value_of("on-invalid") {
+                Some(on_invalid) => on_invalid.parse()?,
+                None => OnInvalid::default(),
+            },
+            on_nan: match matches.value_of("nan") {
+                Some(on_nan) => on_nan.parse()?,
+                None => OnNan::default(),
+            },
+            ..InputPolicy::default()
+        };
+        Ok(InputFormat {
+            delimiter,
+            header: matches.is_present("header"),
+            column,
+            weight_column,
+            weights,
+            group_by,
+            columns,
+            policy,
+        })
+    }
+
+    /// Selects the columns to read with `column_values`.
+    pub fn with_columns(self, columns: Vec<Column>) -> InputFormat {
+        InputFormat { columns, ..self }
+    }
+
+    /// The column holding the values, if one was selected.
+    pub fn column(&self) -> Option<&Column> {
+        self.column.as_ref()
+    }
+
+    /// The character separating fields, or `None` for whitespace.
+    pub fn delimiter(&self) -> Option<char> {
+        self.delimiter
+    }
+
+    /// How invalid lines and NaN values are handled.
+    pub fn policy(&self) -> InputPolicy {
+        self.policy.clone()
+    }
+
+    /// The lines skipped so far by every reader of this format.
+    pub fn skipped(&self) -> &SkippedLines {
+        &self.policy.skipped
+    }
+
+    /// Numbers the lines of the reader from 1, reading the header first if
+    /// there is one.
+    fn numbered_lines(
+        &self,
+        reader: impl BufRead,
+    ) -> Result<(Option<String>, impl Iterator<Item = NumberedLine>), failure::Error> {
+        let mut lines = reader.lines();
+        let header = if self.has_header() {
+            lines.next().transpose()?
+        } else {
+            None
+        };
+        let first = if header.is_some() { 2 } else { 1 };
+        Ok((header, (first..).zip(lines)))
+    }
+
+    /// The kind of weights, if each line holds a weight.
+    pub fn weights(&self) -> Option<Weights> {
+        self.weights
+    }
+
+    /// Whether the first line of input names the columns. Selecting a column
+    /// by name implies a header.
+    pub fn has_header(&self) -> bool {
+        let is_name = |column: &Option<Column>| matches!(column, Some(Column::Name(_)));
+        self.header
+            || is_name(&self.column)
+            || is_name(&self.weight_column)
+            || is_name(&self.group_by)
+            || self
+                .columns
+                .iter()
+                .any(|column| matches!(column, Column::Name(_)))
+    }
+
+    /// A parser for the lines following the header, if any.
+    pub fn parser(&self, header: Option<&str>) -> Result<LineParser, failure::Error> {
+        let names: Vec<&str> = header
+            .map(|header| split(header, self.delimiter))
+            .unwrap_or_default();
+        let index = |column: &Column| column_index(column, &names);
+        let value_index = self.column.as_ref().map_or(Ok(0), index)?;
+        let weight_index = match (&self.weight_column, self.weights) {
+            (Some(column), _) => Some(index(column)?),
+            (None, Some(_)) if self.column.is_none() => Some(1),
+            (None, Some(_)) => {
+                return Err(SamplersError::InvalidArgument {
+                    name: "weights".to_string(),
+                    value: "a weight column is required when selecting a column".to_string(),
+                }
+                .into())
+            }
+            (None, None) => None,
+        };
+        // Without selected columns, a line must hold exactly the expected
+        // fields so that malformed input is not silently truncated.
+        let key_index = self.group_by.as_ref().map(index).transpose()?;
+        let num_fields =
+            if self.column.is_none() && self.weight_column.is_none() && self.group_by.is_none() {
+                Some(weight_index.map_or(1, |_| 2))
+            } else {
+                None
+            };
+        Ok(LineParser {
+            delimiter: self.delimiter,
+            value_index,
+            weight_index,
+            key_index,
+            num_fields,
+        })
+    }
+
+    /// Parses each line of the reader into a value and its weight, which is 1
+    /// for unweighted input.
+    pub fn records(
+        &self,
+        reader: impl BufRead,
+    ) -> Result<impl Iterator<Item = Result<(f64, f64), failure::Error>>, failure::Error> {
+        let (header, lines) = self.numbered_lines(reader)?;
+        let parser = self.parser(header.as_deref())?;
+        let policy = self.policy.clone();
+        Ok(lines.filter_map(move |(number, line)| {
+            policy.screen(
+                line.map_err(failure::Error::from)
+                    .and_then(|line| parser.parse(&line)),
+                || format!("line {}", number),
+                |(value, _weight)| value.is_nan(),
+            )
+        }))
+    }
+
+    /// Parses each line of the reader into the key of its group, a value and
+    /// its weight. The key is empty if no column to group by was given.
+    pub fn keyed_records(
+        &self,
+        reader: impl BufRead,
+    ) -> Result<impl Iterator<Item = Result<(String, f64, f64), failure::Error>>, failure::Error>
+    {
+        let (header, lines) = self.numbered_lines(reader)?;
+        let parser = self.parser(header.as_deref())?;
+        let policy = self.policy.clone();
+        Ok(lines.filter_map(move |(number, line)| {
+            policy.screen(
+                line.map_err(failure::Error::from)
+                    .and_then(|line| parser.parse_keyed(&line)),
+                || format!("line {}", number),
+                |(_key, value, _weight)| value.is_nan(),
+            )
+        }))
+    }
+
+    /// Parses every field of each line of the reader, for reading every
+    /// column at once. Empty fields are missing values, and a line with any
+    /// other field that is not a number is invalid. Returns the names in the
+    /// header, if any, along with the values of each following line.
+    pub fn rows(
+        &self,
+        reader: impl BufRead,
+    ) -> Result<(Option<Vec<String>>, impl Iterator<Item = Row>), failure::Error> {
+        let (header, lines) = self.numbered_lines(reader)?;
+        let delimiter = self.delimiter;
+        let names = header.map(|header| {
+            split(&header, delimiter)
+                .into_iter()
+                .map(str::to_string)
+                .collect()
+        });
+        let parse = move |line: &str| -> Row {
+            split(line, delimiter)
+                .into_iter()
+                .map(|field| match field {
+                    "" => Ok(None),
+                    field => parse_number(field, "value").map(Some),
+                })
+                .collect()
+        };
+        let policy = self.policy.clone();
+        let rows = lines.filter_map(move |(number, line)| {
+            policy.screen(
+                line.map_err(failure::Error::from)
+                    .and_then(|line| parse(&line)),
+                || format!("line {}", number),
+                |values| values.iter().flatten().any(|value| value.is_nan()),
+            )
+        });
+        Ok((names, rows))
+    }
+
+    /// Parses the selected columns of each line of the reader into values.
+    /// Returns the name of each selected column, taken from the header if
+    /// there is one, along with the values of each following line.
+    pub fn column_values(
+        &self,
+        reader: impl BufRead,
+    ) -> Result<(Vec<String>, impl Iterator<Item = Values>), failure::Error> {
+        let (header, lines) = self.lines_with_column_values(reader)?;
+        let (names, _indices) = self.selected_columns(header.as_deref())?;
+        let values = lines.map(|line| line.map(|(_line, values)| values));
+        Ok((names, values))
+    }
+
+    /// The name and 0-based index of each selected column, given the header.
+    fn selected_columns(
+        &self,
+        header: Option<&str>,
+    ) -> Result<(Vec<String>, Vec<usize>), failure::Error> {
+        let header_names: Vec<&str> = header
+            .map(|header| split(header, self.delimiter))
+            .unwrap_or_default();
+        let indices = self
+            .columns
+            .iter()
+            .map(|column| column_index(column, &header_names))
+            .collect::<Result<Vec<usize>, failure::Error>>()?;
+        let names = self
+            .columns
+            .iter()
+            .zip(&indices)
+            .map(|(column, &index)| match (column, header_names.get(index)) {
+                (Column::Index(_), Some(name)) => name.to_string(),
+                (Column::Index(index), None) => index.to_string(),
+                (Column::Name(name), _) => name.to_string(),
+            })
+            .collect();
+        Ok((names, indices))
+    }
+
+    /// Parses the selected columns of each line of the reader into values,
+    /// keeping the line itself so that it can be passed through. Returns the
+    /// header, if any, along with each following line and its values.
+    pub fn lines_with_column_values(
+        &self,
+        reader: impl BufRead,
+    ) -> Result<(Option<String>, impl Iterator<Item = LineWithValues>), failure::Error> {
+        let (header, lines) = self.numbered_lines(reader)?;
+        let (_names, indices) = self.selected_columns(header.as_deref())?;
+        let delimiter = self.delimiter;
+        let policy = self.policy.clone();
+        let parse = move |line: &str| -> Values {
+            let fields = split(line, delimiter);
+            indices
+                .iter()
+                .map(|&index| {
+                    let field =
+                        fields
+                            .get(index)
+                            .ok_or_else(|| SamplersError::InvalidArgument {
+                                name: "line".to_string(),
+                                value: line.to_string(),
+                            })?;
+                    parse_number(field, "value")
+                })
+                .collect()
+        };
+        let lines = lines.filter_map(move |(number, line)| {
+            policy.screen(
+                line.map_err(failure::Error::from).and_then(|line| {
+                    let values = parse(&line)?;
+                    Ok((line, values))
+                }),
+                || format!("line {}", number),
+                |(_line, values)| values.iter().any(|value| value.is_nan()),
+            )
+        });
+        Ok((header, lines))
+    }
+
+    /// Parses each line of the reader into a value, keeping the line itself so
+    /// that it can be passed through. Returns the header, if any, along with
+    /// each following line and its value.
+    pub fn lines_with_values(
+        &self,
+        reader: impl BufRead,
+    ) -> Result<(Option<String>, impl Iterator<Item = LineWithValue>), failure::Error> {
+        let (header, lines) = self.numbered_lines(reader)?;
+        let parser = self.parser(header.as_deref())?;
+        let policy = self.policy.clone();
+        let lines = lines.filter_map(move |(number, line)| {
+            policy.screen(
+                line.map_err(failure::Error::from).and_then(|line| {
+                    let (value, _weight) = parser.parse(&line)?;
+                    Ok((line, value))
+                }),
+                || format!("line {}", number),
+                |(_line, value)| value.is_nan(),
+            )
+        });
+        Ok((header, lines))
+    }
+
+    /// Reads the selected column of each line of the reader as text, for
+    /// categorical data. Without a selected column, each line without its
+    /// surrounding whitespace is a key. Empty lines are skipped.
+    pub fn keys(
+        &self,
+        reader: impl BufRead,
+    ) -> Result<impl Iterator<Item = Result<String, failure::Error>>, failure::Error> {
+        let (header, lines) = self.numbered_lines(reader)?;
+        let names: Vec<&str> = header
+            .as_deref()
+            .map(|header| split(header, self.delimiter))
+            .unwrap_or_default();
+        let index = self
+            .column
+            .as_ref()
+            .map(|column| column_index(column, &names))
+            .transpose()?;
+        let delimiter = self.delimiter;
+        let policy = self.policy.clone();
+        Ok(lines.filter_map(move |(number, line)| {
+            if matches!(line, Ok(ref line) if line.trim().is_empty()) {
+                return None;
+            }
+            let key = line
+                .map_err(failure::Error::from)
+                .and_then(|line| match index {
+                    Some(index) => split(&line, delimiter)
+                        .get(index)
+                        .map(|field| field.to_string())
+                        .ok_or_else(|| {
+                            SamplersError::InvalidArgument {
+                                name: "line".to_string(),
+                                value: line.to_string(),
+                            }
+                            .into()
+                        }),
+                    None => Ok(line.trim().to_string()),
+                });
+            policy.screen(key, || format!("line {}", number), |_key| false)
+        }))
+    }
+
+    /// Parses each line of the reader into a value, ignoring any weights.
+    pub fn values(
+        &self,
+        reader: impl BufRead,
+    ) -> Result<impl Iterator<Item = Result<f64, failure::Error>>, failure::Error> {
+        Ok(self
+            .records(reader)?
+            .map(|record| record.map(|(value, _weight)| value)))
+    }
+}
+
+/// Parses a delimiter, which is a single character or `tab`.
+fn parse_delimiter(s: &str) -> Result<char, failure::Error> {
+    let mut chars = s.chars();
+    match (s, chars.next(), chars.next()) {
+        ("tab", _, _) | ("\\t", _, _) => Ok('\t'),
+        (_, Some(delimiter), None) => Ok(delimiter),
+        _ => Err(SamplersError::InvalidArgument {
+            name: "delimiter".to_string(),
+            value: s.to_string(),
+        }
+        .into()),
+    }
+}
+
+/// The 0-based index of a column, given the names in the header.
+fn column_index(column: &Column, names: &[&str]) -> Result<usize, failure::Error> {
+    match column {
+        Column::Index(index) => Ok(index - 1),
+        Column::Name(name) => names.iter().position(|field| field == name).ok_or_else(|| {
+            SamplersError::InvalidArgument {
+                name: "column".to_string(),
+                value: name.to_string(),
+            }
+            .into()
+        }),
+    }
+}
+
+/// Parses a field as a number, naming the field in the error.
+fn parse_number(field: &str, name: &str) -> Result<f64, failure::Error> {
+    field.parse::<f64>().map_err(|_| {
+        SamplersError::InvalidArgument {
+            name: name.to_string(),
+            value: field.to_string(),
+        }
+        .into()
+    })
+}
+
+fn split(line: &str, delimiter: Option<char>) -> Vec<&str> {
+    match delimiter {
+        // Surrounding whitespace and quotes are not part of a field.
+        Some(delimiter) => line
+            .split(delimiter)
+            .map(|field| field.trim().trim_matches('"'))
+            .collect(),
+        None => line.split_whitespace().collect(),
+    }
+}
+
+/// Finds the value and weight in a line of input.
+#[derive(Debug, Clone)]
+pub struct LineParser {
+    delimiter: Option<char>,
+    value_index: usize,
+    weight_index: Option<usize>,
+    key_index: Option<usize>,
+    num_fields: Option<usize>,
+}
+
+impl LineParser {
+    pub fn parse(&self, line: &str) -> Result<(f64, f64), failure::Error> {
+        let (_key, value, weight) = self.parse_keyed(line)?;
+        Ok((value, weight))
+    }
+
+    pub fn parse_keyed(&self, line: &str) -> Result<(String, f64, f64), failure::Error> {
+        let invalid = || SamplersError::InvalidArgument {
+            name: "line".to_string(),
+            value: line.to_string(),
+        };
+        let fields = split(line, self.delimiter);
+        if self.num_fields.map_or(false, |n| n != fields.len()) {
+            return Err(invalid().into());
+        }
+        let field = |index: usize| fields.get(index).ok_or_else(invalid);
+        let value = parse_number(field(self.value_index)?, "value")?;
+        let weight = match self.weight_index {
+            Some(index) => parse_number(field(index)?, "weight")?,
+            None => 1.0,
+        };
+        let key = match self.key_index {
+            Some(index) => field(index)?.to_string(),
+            None => String::new(),
+        };
+        Ok((key, value, weight))
+    }
+}
+
+#[test]
+fn test_input_format() -> Result<(), failure::Error> {
+    let read = |format: &InputFormat, input: &str| -> Result<Vec<(f64, f64)>, failure::Error> {
+        format.records(input.as_bytes())?.collect()
+    };
+
+    let format = InputFormat::default();
+    assert_eq!(read(&format, "1.5\n-2\n")?, vec![(1.5, 1.0), (-2.0, 1.0)]);
+    assert!(read(&format, "1 2\n").is_err());
+    assert!(read(&format, "x\n").is_err());
+
+    let format = InputFormat {
+        weights: Some(Weights::Frequency),
+        ..InputFormat::default()
+    };
+    assert_eq!(
+        read(&format, "1 2\n3\t0.5\n")?,
+        vec![(1.0, 2.0), (3.0, 0.5)]
+    );
+    assert!(read(&format, "1\n").is_err());
+
+    let csv = "time,latency,\"count\"\n1,0.25,3\n2,0.5,1\n";
+    let format = InputFormat {
+        delimiter: Some(','),
+        column: Some("latency".parse()?),
+        weight_column: Some("count".parse()?),
+        weights: Some(Weights::Frequency),
+        ..InputFormat::default()
+    };
+    assert_eq!(read(&format, csv)?, vec![(0.25, 3.0), (0.5, 1.0)]);
+
+    let format = InputFormat {
+        delimiter: Some(parse_delimiter("tab")?),
+        header: true,
+        column: Some("3".parse()?),
+        ..InputFormat::default()
+    };
+    assert_eq!(read(&format, "a\tb\tc\n1\t2\t3\n")?, vec![(3.0, 1.0)]);
+
+    let format = InputFormat {
+        delimiter: Some(','),
+        column: Some("2".parse()?),
+        group_by: Some("time".parse()?),
+        ..InputFormat::default()
+    };
+    let records = format
+        .keyed_records(csv.as_bytes())?
+        .collect::<Result<Vec<(String, f64, f64)>, failure::Error>>()?;
+    assert_eq!(
+        records,
+        vec![("1".to_string(), 0.25, 1.0), ("2".to_string(), 0.5, 1.0)]
+    );
+
+    let format = InputFormat {
+        delimiter: Some(','),
+        header: true,
+        ..InputFormat::default()
+    };
+    let (names, rows) = format.rows(csv.as_bytes())?;
+    assert_eq!(
+        names,
+        Some(vec![
+            "time".to_string(),
+            "latency".to_string(),
+            "count".to_string()
+        ])
+    );
+    let rows = rows.collect::<Result<Vec<Vec<Option<f64>>>, failure::Error>>()?;
+    assert_eq!(rows[1], vec![Some(2.0), Some(0.5), Some(1.0)]);
+    let rows = format
+        .rows("a,b\n1,\nx,2\n".as_bytes())?
+        .1
+        .collect::<Vec<Row>>();
+    assert_eq!(rows[0].as_ref().unwrap(), &vec![Some(1.0), None]);
+    assert_eq!(
+        rows[1].as_ref().unwrap_err().to_string(),
+        "Could not parse line 3: Invalid value: x"
+    );
+
+    let format = InputFormat {
+        delimiter: Some(','),
+        columns: vec!["count".parse()?, "1".parse()?],
+        ..InputFormat::default()
+    };
+    let (names, values) = format.column_values(csv.as_bytes())?;
+    assert_eq!(names, vec!["count", "time"]);
+    let values = values.collect::<Result<Vec<Vec<f64>>, failure::Error>>()?;
+    assert_eq!(values, vec![vec![3.0, 1.0], vec![1.0, 2.0]]);
+
+    let format = InputFormat {
+        delimiter: Some(','),
+        column: Some("latency".parse()?),
+        ..InputFormat::default()
+    };
+    let keys = format
+        .keys(format!("{}\n", csv).as_bytes())?
+        .collect::<Result<Vec<String>, failure::Error>>()?;
+    assert_eq!(keys, vec!["0.25", "0.5"]);
+    let keys = InputFormat::default()
+        .keys(" red \n\nblue green\n".as_bytes())?
+        .collect::<Result<Vec<String>, failure::Error>>()?;
+    assert_eq!(keys, vec!["red", "blue green"]);
+
+    let format = InputFormat {
+        delimiter: Some(','),
+        column: Some("missing".parse()?),
+        ..InputFormat::default()
+    };
+    assert!(read(&format, csv).is_err());
+    assert!("0".parse::<Column>().is_err());
+    assert!(parse_delimiter("ab").is_err());
+    Ok(())
+}
+
+#[test]
+fn test_input_policy() -> Result<(), failure::Error> {
+    let read = |policy: &InputPolicy| -> Result<Vec<f64>, failure::Error> {
+        let format = InputFormat {
+            header: true,
+            policy: policy.clone(),
+            ..InputFormat::default()
+        };
+        format
+            .values("value\n1\nx\n2\nNaN\n3 4\n".as_bytes())?
+            .collect()
+    };
+
+    let error = read(&InputPolicy::default()).unwrap_err().to_string();
+    assert_eq!(error, "Could not parse line 3: Invalid value: x");
+
+    let skip_invalid = InputPolicy {
+        on_invalid: OnInvalid::Skip,
+        on_nan: OnNan::Propagate,
+        ..InputPolicy::default()
+    };
+    let values = read(&skip_invalid)?;
+    assert_eq!(values.len(), 3);
+    assert!(values[2].is_nan());
+    assert_eq!(skip_invalid.skipped.invalid(), 2);
+    assert_eq!(skip_invalid.skipped.nan(), 0);
+
+    // Copies of a policy count the lines they skip together.
+    let skip_all = InputPolicy {
+        on_invalid: OnInvalid::Skip,
+        on_nan: OnNan::Skip,
+        ..InputPolicy::default()
+    };
+    assert_eq!(read(&skip_all)?, vec![1.0, 2.0]);
+    assert_eq!(read(&skip_all.clone())?, vec![1.0, 2.0]);
+    assert_eq!(skip_all.skipped.invalid(), 4);
+    assert_eq!(skip_all.skipped.nan(), 2);
+    assert_eq!(
+        skip_all.skipped.report(),
+        Some("Skipped 6 lines: 4 invalid, 2 NaN".to_string())
+    );
+    assert_eq!(InputPolicy::default().skipped.report(), None);
+
+    // Skipping NaN values does not skip invalid lines.
+    let nan_only = InputPolicy {
+        on_invalid: OnInvalid::Error,
+        on_nan: OnNan::Skip,
+        ..InputPolicy::default()
+    };
+    assert!(read(&nan_only).is_err());
+    assert!("ignore".parse::<OnInvalid>().is_err());
+    Ok(())
+}
@@ -1,17 +1,38 @@
 #[macro_use]
 extern crate failure;
 
-use std::io::BufRead;
-
-use clap::{value_t, App, AppSettings, Arg, ArgMatches, SubCommand};
+use clap::{value_t, App, AppSettings, Arg, ArgGroup, ArgMatches, SubCommand};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
 
+mod bootstrap;
+mod compare;
+mod correlate;
 mod distributions;
+mod entropy;
+mod expression;
+mod fit;
+mod gof;
 mod histogram;
+mod inference;
+mod input;
+mod normality;
+mod outliers;
+mod parallel;
+mod regress;
 mod render;
+mod robust;
+mod rolling;
+mod series;
+mod sketch;
 mod summary;
+mod summation;
+mod transform;
 
 use histogram::Histogram;
+use input::InputFormat;
 use summary::{DistributionSummary, Observer};
+use summation::Precision;
 
 #[derive(Debug, Fail)]
 enum SamplersError {
@@ -19,8 +40,19 @@ enum SamplersError {
     CouldNotObserveValue { value: f64 },
     #[fail(display = "Could not calculate summary statistic: {}", name)]
     CouldNotCalculateSummaryStatistic { name: String },
+    #[fail(display = "Could not fit a {} distribution to the values", family)]
+    CouldNotFitDistribution { family: String },
+    #[fail(display = "Invalid {}: {}", name, value)]
+    InvalidArgument { name: String, value: String },
+    #[fail(display = "Expected a positive value: {}", value)]
+    NonPositiveValue { value: f64 },
+    #[fail(display = "Could not parse {}: {}", location, reason)]
+    InvalidInput { location: String, reason: String },
 }
 
+/// The percentage trimmed from each end without `--trim`.
+const DEFAULT_TRIM: &str = "10";
+
 enum InputMethod {
     Manual,
     Piped,
@@ -32,11 +64,21 @@ enum OutputMethod {
     Piped,
 }
 
+/// A random number generator seeded by the global `--seed` option if it is
+/// present, or from system entropy otherwise.
+fn rng(matches: &ArgMatches) -> Result<StdRng, failure::Error> {
+    if matches.is_present("seed") {
+        Ok(StdRng::seed_from_u64(clap::value_t!(matches, "seed", u64)?))
+    } else {
+        Ok(StdRng::from_entropy())
+    }
+}
+
 fn gaussian(matches: &ArgMatches) -> Result<(), failure::Error> {
     let num_experiments = clap::value_t!(matches, "num_experiments", usize)?;
     let mean = clap::value_t!(matches, "mean", f64)?;
     let variance = clap::value_t!(matches, "variance", f64)?;
-    distributions::gaussian(mean, variance)?
+    distributions::gaussian(mean, variance, rng(matches)?)?
         .take(num_experiments)
         .for_each(|v| println!("{}", v));
     Ok(())
@@ -45,7 +87,7 @@ fn gaussian(matches: &ArgMatches) -> Result<(), failure::Error> {
 fn poisson(matches: &ArgMatches) -> Result<(), failure::Error> {
     let num_experiments = clap::value_t!(matches, "num_experiments", usize)?;
     let lambda = clap::value_t!(matches, "lambda", f64)?;
-    distributions::poisson(lambda)?
+    distributions::poisson(lambda, rng(matches)?)?
         .take(num_experiments)
         .for_each(|v| println!("{}", v));
     Ok(())
@@ -54,7 +96,27 @@ fn poisson(matches: &ArgMatches) -> Result<(), failure::Error> {
 fn exponential(matches: &ArgMatches) -> Result<(), failure::Error> {
     let num_experiments = clap::value_t!(matches, "num_experiments", usize)?;
     let lambda = clap::value_t!(matches, "lambda", f64)?;
-    distributions::exponential(lambda)?
+    distributions::exponential(lambda, rng(matches)?)?
+        .take(num_experiments)
+        .for_each(|v| println!("{}", v));
+    Ok(())
+}
+
+fn gamma(matches: &ArgMatches) -> Result<(), failure::Error> {
+    let num_experiments = clap::value_t!(matches, "num_experiments", usize)?;
+    let shape = clap::value_t!(matches, "shape", f64)?;
+    let rate = clap::value_t!(matches, "rate", f64)?;
+    distributions::gamma(shape, rate, rng(matches)?)?
+        .take(num_experiments)
+        .for_each(|v| println!("{}", v));
+    Ok(())
+}
+
+fn lognormal(matches: &ArgMatches) -> Result<(), failure::Error> {
+    let num_experiments = clap::value_t!(matches, "num_experiments", usize)?;
+    let mean = clap::value_t!(matches, "mean", f64)?;
+    let variance = clap::value_t!(matches, "variance", f64)?;
+    distributions::lognormal(mean, variance, rng(matches)?)?
         .take(num_experiments)
         .for_each(|v| println!("{}", v));
     Ok(())
@@ -66,14 +128,14 @@ fn uniform(matches: &ArgMatches) -> Result<(), failure::Error> {
         Some("continuous") => {
             let lower = clap::value_t!(matches, "lower", f64)?;
             let upper = clap::value_t!(matches, "upper", f64)?;
-            distributions::continuous_uniform(lower, upper)
+            distributions::continuous_uniform(lower, upper, rng(matches)?)
                 .take(num_experiments)
                 .for_each(|v| println!("{}", v));
         }
         Some("discrete") => {
             let lower = clap::value_t!(matches, "lower", i64)?;
             let upper = clap::value_t!(matches, "upper", i64)?;
-            distributions::discrete_uniform(lower, upper)
+            distributions::discrete_uniform(lower, upper, rng(matches)?)
                 .take(num_experiments)
                 .for_each(|v| println!("{}", v));
         }
@@ -86,31 +148,354 @@ fn binomial(matches: &ArgMatches) -> Result<(), failure::Error> {
     let num_experiments = clap::value_t!(matches, "num_experiments", usize)?;
     let num_trials = clap::value_t!(matches, "num-trials", u64)?;
     let probability = clap::value_t!(matches, "probability", f64)?;
-    distributions::binomial(num_trials, probability)?
+    distributions::binomial(num_trials, probability, rng(matches)?)?
         .take(num_experiments)
         .for_each(|v| println!("{}", v));
     Ok(())
 }
 
-fn summarize(_matches: &ArgMatches, input_method: InputMethod) -> Result<(), failure::Error> {
-    let mut summary = DistributionSummary::default();
-    match input_method {
-        InputMethod::Manual => {
-            for value in get_results_from_stdin(&mut std::io::stdin()) {
-                summary.observe(&value?)?;
+fn summarize(matches: &ArgMatches, input_method: InputMethod) -> Result<(), failure::Error> {
+    let output_format = clap::value_t!(matches, "format", render::OutputFormat)?;
+    let confidence = if matches.is_present("confidence") {
+        let level = clap::value_t!(matches, "confidence", f64)?;
+        Some(inference::validate_confidence_level(level)?)
+    } else {
+        None
+    };
+    let format = InputFormat::from_matches(matches)?;
+    summarize_input(matches, input_method, &format, output_format, confidence)?;
+    report_skipped(&format);
+    Ok(())
+}
+
+/// Summarizes the input in the mode selected by the flags.
+fn summarize_input(
+    matches: &ArgMatches,
+    input_method: InputMethod,
+    format: &InputFormat,
+    output_format: render::OutputFormat,
+    confidence: Option<f64>,
+) -> Result<(), failure::Error> {
+    if matches.is_present("all-columns") {
+        return summarize_columns(matches, format, output_format);
+    }
+    if matches.is_present("group-by") {
+        return summarize_groups(matches, format, output_format);
+    }
+    let name = matches.value_of("column").unwrap_or("value");
+    let report =
+        |summary: &DistributionSummary| print_summary(summary, name, output_format, confidence);
+    if matches.is_present("every") {
+        let interval = clap::value_t!(matches, "every", input::Interval)?;
+        return summarize_every(matches, format, interval, report);
+    }
+    if matches.is_present("robust") {
+        let trim: f64 = matches.value_of("trim").unwrap_or(DEFAULT_TRIM).parse()?;
+        let trim = robust::validate_trim(trim / 100.0)?;
+        // Robust statistics need every value, so this takes more than one
+        // pass.
+        let values = match matches.value_of("file") {
+            Some(path) => get_values_from_file(path, format)?,
+            None => get_values_from_stdin(format)?,
+        };
+        let mut summary = DistributionSummary::default();
+        summary.observe_many(values.iter())?;
+        let robust = robust::RobustSummary::new(values, trim)?;
+        if output_format == render::OutputFormat::Text {
+            report(&summary)?;
+            println!("{}", robust);
+            return Ok(());
+        }
+        // The robust statistics follow the others in the same row.
+        return render::render_extended_summaries(
+            &[(name.to_string(), summary)],
+            &robust::RobustSummary::COLUMNS,
+            |_summary| robust.statistics().to_vec(),
+            output_format,
+            std::io::stdout(),
+        );
+    }
+    let num_threads = clap::value_t!(matches, "threads", usize)?;
+    let precision = clap::value_t!(matches, "precision", Precision)?;
+    let mut summary = DistributionSummary::with_weights(format.weights().unwrap_or_default())
+        .with_precision(precision);
+    match (matches.value_of("file"), input_method) {
+        (Some(path), _) if num_threads > 1 && std::fs::metadata(path)?.is_file() => {
+            summary = parallel::summarize_file(
+                std::path::Path::new(path),
+                num_threads,
+                format,
+                precision,
+            )?;
+        }
+        (Some(path), _) => {
+            let file = std::io::BufReader::new(std::fs::File::open(path)?);
+            for record in format.records(file)? {
+                let (value, weight) = record?;
+                summary.observe_weighted(&value, weight)?;
             }
         }
-        InputMethod::Piped => {
-            summary.observe_many(get_values_from_stdin()?.iter())?;
+        (None, InputMethod::Manual) => {
+            for record in format.records(std::io::stdin().lock())? {
+                let (value, weight) = record?;
+                summary.observe_weighted(&value, weight)?;
+            }
+        }
+        (None, InputMethod::Piped) => {
+            for (value, weight) in get_records_from_stdin(format)? {
+                summary.observe_weighted(&value, weight)?;
+            }
+        }
+    }
+    report(&summary)
+}
+
+/// Prints a summary in the given format, followed by the confidence intervals
+/// at the given level. In the CSV and JSON formats, the bounds of each
+/// interval are further columns of the row.
+fn print_summary(
+    summary: &DistributionSummary,
+    name: &str,
+    output_format: render::OutputFormat,
+    confidence: Option<f64>,
+) -> Result<(), failure::Error> {
+    if output_format != render::OutputFormat::Text {
+        let summaries = [(name.to_string(), summary.clone())];
+        let level = match confidence {
+            Some(level) => level,
+            None => return render::render_summaries(&summaries, output_format, std::io::stdout()),
+        };
+        let mut columns = vec![
+            "mean_lower",
+            "mean_upper",
+            "variance_lower",
+            "variance_upper",
+        ];
+        if summary.is_binary() {
+            columns.extend(&["proportion_lower", "proportion_upper"]);
         }
+        let bounds = |interval: Option<inference::ConfidenceInterval>| {
+            interval.map_or([std::f64::NAN; 2], |i| [i.lower, i.upper])
+        };
+        return render::render_extended_summaries(
+            &summaries,
+            &columns,
+            |summary| {
+                let mut intervals = vec![
+                    summary.mean_confidence_interval(level),
+                    summary.variance_confidence_interval(level),
+                ];
+                if summary.is_binary() {
+                    intervals.push(summary.proportion_confidence_interval(level));
+                }
+                intervals.into_iter().flat_map(bounds).collect()
+            },
+            output_format,
+            std::io::stdout(),
+        );
     }
     println!("{}", summary);
+    if let Some(level) = confidence {
+        let percent = level * 100.0;
+        let format_interval = |interval: Option<inference::ConfidenceInterval>| {
+            interval.map_or_else(|| std::f64::NAN.to_string(), |i| i.to_string())
+        };
+        println!(
+            "Mean {}% confidence interval: {}",
+            percent,
+            format_interval(summary.mean_confidence_interval(level))
+        );
+        println!(
+            "Variance {}% confidence interval: {}",
+            percent,
+            format_interval(summary.variance_confidence_interval(level))
+        );
+        if summary.is_binary() {
+            println!(
+                "Proportion {}% Wilson interval: {}",
+                percent,
+                format_interval(summary.proportion_confidence_interval(level))
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Summarizes values as they are read, reporting the summary after every
+/// interval and again at the end of the input. The input is read on its own
+/// thread, so that a report is due on time even while no input arrives, such
+/// as when reading from `tail -f`. With `--reset`, each report summarizes only
+/// the values since the last one.
+fn summarize_every(
+    matches: &ArgMatches,
+    format: &InputFormat,
+    interval: input::Interval,
+    report: impl Fn(&DistributionSummary) -> Result<(), failure::Error>,
+) -> Result<(), failure::Error> {
+    let (sender, receiver) = std::sync::mpsc::sync_channel(1024);
+    let path = matches.value_of("file").map(str::to_string);
+    let reader_format = format.clone();
+    std::thread::spawn(move || {
+        let send_records = || -> Result<(), failure::Error> {
+            let reader = open_input(path.as_deref())?;
+            for record in reader_format.records(reader)? {
+                if sender.send(record).is_err() {
+                    break;
+                }
+            }
+            Ok(())
+        };
+        if let Err(error) = send_records() {
+            // The receiver may have already stopped after an earlier error.
+            let _ = sender.send(Err(error));
+        }
+    });
+
+    let weights = format.weights().unwrap_or_default();
+    let precision = clap::value_t!(matches, "precision", Precision)?;
+    let reset = matches.is_present("reset");
+    let mut summary = DistributionSummary::with_weights(weights).with_precision(precision);
+    let mut num_lines: u64 = 0;
+    // Whether values were observed since the last report, and whether there
+    // has been any report at all.
+    let (mut pending, mut reported) = (false, false);
+    let mut deadline = match interval {
+        input::Interval::Duration(duration) => Some(std::time::Instant::now() + duration),
+        input::Interval::Lines(_) => None,
+    };
+    loop {
+        let record = match deadline {
+            Some(deadline) => {
+                let timeout = deadline.saturating_duration_since(std::time::Instant::now());
+                match receiver.recv_timeout(timeout) {
+                    Ok(record) => Some(record),
+                    Err(std::sync::mpsc::RecvTimeoutError::Timeout) => None,
+                    Err(std::sync::mpsc::RecvTimeoutError::Disconnected) => break,
+                }
+            }
+            None => match receiver.recv() {
+                Ok(record) => Some(record),
+                Err(_) => break,
+            },
+        };
+        if let Some(record) = record {
+            let (value, weight) = record?;
+            summary.observe_weighted(&value, weight)?;
+            num_lines += 1;
+            pending = true;
+        }
+        let due = match (interval, deadline) {
+            (input::Interval::Lines(lines), _) => pending && num_lines % lines == 0,
+            (_, Some(deadline)) => std::time::Instant::now() >= deadline,
+            (_, None) => false,
+        };
+        if due {
+            if reported {
+                println!();
+            }
+            report(&summary)?;
+            if reset {
+                summary = DistributionSummary::with_weights(weights).with_precision(precision);
+            }
+            pending = false;
+            reported = true;
+            if let input::Interval::Duration(duration) = interval {
+                deadline = Some(std::time::Instant::now() + duration);
+            }
+        }
+    }
+    if pending || !reported {
+        if reported {
+            println!();
+        }
+        report(&summary)?;
+    }
     Ok(())
 }
 
+/// Summarizes every column of delimited input. Empty fields are skipped, and
+/// lines with any other field that is not a number are handled by the
+/// invalid line policy.
+fn summarize_columns(
+    matches: &ArgMatches,
+    format: &InputFormat,
+    output_format: render::OutputFormat,
+) -> Result<(), failure::Error> {
+    let reader = open_input(matches.value_of("file"))?;
+    let precision = clap::value_t!(matches, "precision", Precision)?;
+    let (names, rows) = format.rows(reader)?;
+    let mut columns: Vec<DistributionSummary> = Vec::new();
+    for row in rows {
+        let row = row?;
+        if columns.len() < row.len() {
+            columns.resize_with(row.len(), || {
+                DistributionSummary::default().with_precision(precision)
+            });
+        }
+        for (value, summary) in row.iter().zip(columns.iter_mut()) {
+            if let Some(value) = value {
+                summary.observe(value)?;
+            }
+        }
+    }
+    let summaries: Vec<(String, DistributionSummary)> = columns
+        .into_iter()
+        .enumerate()
+        .filter(|(_, summary)| summary.count() > 0)
+        .map(|(i, summary)| {
+            let name = names
+                .as_ref()
+                .and_then(|names| names.get(i).cloned())
+                .unwrap_or_else(|| (i + 1).to_string());
+            (name, summary)
+        })
+        .collect();
+    render::render_summaries(&summaries, output_format, std::io::stdout())
+}
+
+/// Summarizes the values of each group in a single pass, where lines are
+/// grouped by the distinct keys in the `--group-by` column.
+fn summarize_groups(
+    matches: &ArgMatches,
+    format: &InputFormat,
+    output_format: render::OutputFormat,
+) -> Result<(), failure::Error> {
+    let reader = open_input(matches.value_of("file"))?;
+    let weights = format.weights().unwrap_or_default();
+    let precision = clap::value_t!(matches, "precision", Precision)?;
+    let mut groups: std::collections::BTreeMap<String, DistributionSummary> =
+        std::collections::BTreeMap::new();
+    for record in format.keyed_records(reader)? {
+        let (key, value, weight) = record?;
+        groups
+            .entry(key)
+            .or_insert_with(|| DistributionSummary::with_weights(weights).with_precision(precision))
+            .observe_weighted(&value, weight)?;
+    }
+    let mut groups: Vec<(String, DistributionSummary)> = groups.into_iter().collect();
+    let top = if matches.is_present("top") {
+        Some(clap::value_t!(matches, "top", usize)?)
+    } else {
+        None
+    };
+    render::rank_summaries(&mut groups, matches.value_of("sort-by"), top);
+    render::render_summaries(&groups, output_format, std::io::stdout())
+}
+
 fn histogram(matches: &ArgMatches, output_method: OutputMethod) -> Result<(), failure::Error> {
     let num_buckets: usize = clap::value_t!(matches, "num-buckets", usize)?;
     let display_size: usize = clap::value_t!(matches, "display-size", usize)?;
+    let format = InputFormat::from_matches(matches)?;
+    let weighted = format.weights().is_some();
+    let echo = |value: f64, weight: f64| {
+        if output_method == OutputMethod::Piped {
+            if weighted {
+                println!("{} {}", value, weight);
+            } else {
+                println!("{}", value);
+            }
+        }
+    };
     let histogram = match (
         clap::value_t!(matches, "min", f64),
         clap::value_t!(matches, "max", f64),
@@ -118,22 +503,24 @@ fn histogram(matches: &ArgMatches, output_method: OutputMethod) -> Result<(), fa
         (Ok(min), Ok(max)) => {
             // Compute histogram in a single pass.
             let mut histogram = Histogram::with_bounds(min, max, num_buckets);
-            get_results_from_stdin(&mut std::io::stdin()).try_for_each(|result| {
-                let value = result?;
-                if output_method == OutputMethod::Piped {
-                    println!("{}", value);
-                }
-                histogram.observe(&value)
-            })?;
+            format.records(std::io::stdin().lock())?.try_for_each(
+                |result| -> Result<(), failure::Error> {
+                    let (value, weight) = result?;
+                    echo(value, weight);
+                    histogram.observe_weighted(&value, weight)
+                },
+            )?;
             histogram
         }
         (min_result, max_result) => {
-            let values: Vec<f64> = get_values_from_stdin()?;
-            if output_method == OutputMethod::Piped {
-                values.iter().for_each(|value| println!("{}", value));
-            }
+            let values = get_records_from_stdin(&format)?;
+            values
+                .iter()
+                .for_each(|&(value, weight)| echo(value, weight));
             let mut summary = DistributionSummary::default();
-            summary.observe_many(values.iter())?;
+            for (value, weight) in &values {
+                summary.observe_weighted(value, *weight)?;
+            }
             let min = min_result.or_else(|_| {
                 summary
                     .min()
@@ -149,7 +536,9 @@ fn histogram(matches: &ArgMatches, output_method: OutputMethod) -> Result<(), fa
                     })
             })?;
             let mut histogram = Histogram::with_bounds(min, max, num_buckets);
-            histogram.observe_many(values.iter())?;
+            for (value, weight) in &values {
+                histogram.observe_weighted(value, *weight)?;
+            }
             histogram
         }
     };
@@ -157,24 +546,72 @@ fn histogram(matches: &ArgMatches, output_method: OutputMethod) -> Result<(), fa
     match output_method {
         OutputMethod::Console => render::render_buckets(&buckets, display_size, std::io::stdout()),
         OutputMethod::Piped => render::render_buckets(&buckets, display_size, std::io::stderr()),
-    }
+    }?;
+    report_skipped(&format);
+    Ok(())
 }
 
-fn mean(_matches: &ArgMatches, input_method: InputMethod) -> Result<(), failure::Error> {
+fn mean(matches: &ArgMatches, input_method: InputMethod) -> Result<(), failure::Error> {
+    let format = InputFormat::from_matches(matches)?;
+    let kind = matches.value_of("type").unwrap_or("arithmetic");
+    if kind != "arithmetic" {
+        if format.weights().is_some() {
+            return Err(SamplersError::InvalidArgument {
+                name: "type".to_string(),
+                value: format!(
+                    "weights are only supported by the arithmetic mean, not {}",
+                    kind
+                ),
+            }
+            .into());
+        }
+        let values = get_values_from_stdin(&format)?.into_iter();
+        let mean = match kind {
+            "geometric" => summary::geometric_mean(values)?,
+            "harmonic" => summary::harmonic_mean(values)?,
+            "power" => summary::power_mean(values, clap::value_t!(matches, "exponent", f64)?)?,
+            "log" => summary::log_mean_exp(values)?,
+            _ => unreachable!(),
+        };
+        println!("{}", mean);
+        report_skipped(&format);
+        return Ok(());
+    }
+    let precision = clap::value_t!(matches, "precision", Precision)?;
+    if format.weights().is_some() || precision == Precision::High {
+        let summary = summary_from_stdin(&format, precision)?;
+        println!("{}", summary.mean().unwrap_or(std::f64::NAN));
+        report_skipped(&format);
+        return Ok(());
+    }
     let mean = match input_method {
-        InputMethod::Manual => summary::mean_result(get_results_from_stdin(&mut std::io::stdin()))?,
-        InputMethod::Piped => summary::mean(get_values_from_stdin()?.into_iter()),
+        InputMethod::Manual => summary::mean_result(format.values(std::io::stdin().lock())?)?,
+        InputMethod::Piped => summary::mean(get_values_from_stdin(&format)?.into_iter()),
     };
     println!("{}", mean);
+    report_skipped(&format);
     Ok(())
 }
 
 fn variance(matches: &ArgMatches, input_method: InputMethod) -> Result<(), failure::Error> {
-    let (population_variance, sample_variance) = match input_method {
-        InputMethod::Manual => {
-            summary::variance_result(get_results_from_stdin(&mut std::io::stdin()))?
+    let format = InputFormat::from_matches(matches)?;
+    let precision = clap::value_t!(matches, "precision", Precision)?;
+    // Weighted values and high precision need the full summary.
+    let summarized = format.weights().is_some() || precision == Precision::High;
+    let (population_variance, sample_variance) = match (summarized, input_method) {
+        (true, _) => {
+            let summary = summary_from_stdin(&format, precision)?;
+            (
+                summary.population_variance().unwrap_or(std::f64::NAN),
+                summary.variance().unwrap_or(std::f64::NAN),
+            )
+        }
+        (false, InputMethod::Manual) => {
+            summary::variance_result(format.values(std::io::stdin().lock())?)?
+        }
+        (false, InputMethod::Piped) => {
+            summary::variance(get_values_from_stdin(&format)?.into_iter())
         }
-        InputMethod::Piped => summary::variance(get_values_from_stdin()?.into_iter()),
     };
     println!(
         "{}",
@@ -184,19 +621,667 @@ fn variance(matches: &ArgMatches, input_method: InputMethod) -> Result<(), failu
             _ => unreachable!(),
         }
     );
+    report_skipped(&format);
+    Ok(())
+}
+
+fn sum(matches: &ArgMatches) -> Result<(), failure::Error> {
+    let format = InputFormat::from_matches(matches)?;
+    let precision = clap::value_t!(matches, "precision", Precision)?;
+    let values = format.values(std::io::stdin().lock())?;
+    println!("{}", series::sum(values, precision)?);
+    report_skipped(&format);
+    Ok(())
+}
+
+/// Prints each value of a series derived from the values read from stdin, as
+/// soon as it is known.
+fn print_series(values: impl Iterator<Item = series::Value>) -> Result<(), failure::Error> {
+    for value in values {
+        println!("{}", value?);
+    }
+    Ok(())
+}
+
+fn cumsum(matches: &ArgMatches) -> Result<(), failure::Error> {
+    let format = InputFormat::from_matches(matches)?;
+    let precision = clap::value_t!(matches, "precision", Precision)?;
+    let values = format.values(std::io::stdin().lock())?;
+    print_series(series::cumulative_sums(values, precision))?;
+    report_skipped(&format);
+    Ok(())
+}
+
+fn cumprod(matches: &ArgMatches) -> Result<(), failure::Error> {
+    let format = InputFormat::from_matches(matches)?;
+    let values = format.values(std::io::stdin().lock())?;
+    print_series(series::cumulative_products(values))?;
+    report_skipped(&format);
+    Ok(())
+}
+
+fn diff(matches: &ArgMatches) -> Result<(), failure::Error> {
+    let format = InputFormat::from_matches(matches)?;
+    let lag = clap::value_t!(matches, "lag", usize)?;
+    let values = format.values(std::io::stdin().lock())?;
+    print_series(series::differences(values, lag)?)?;
+    report_skipped(&format);
+    Ok(())
+}
+
+fn rank(matches: &ArgMatches) -> Result<(), failure::Error> {
+    let format = InputFormat::from_matches(matches)?;
+    let values = get_values_from_stdin(&format)?;
+    for rank in summary::ranks(&values) {
+        println!("{}", rank);
+    }
+    report_skipped(&format);
+    Ok(())
+}
+
+fn transform(matches: &ArgMatches) -> Result<(), failure::Error> {
+    use transform::Transform;
+
+    let format = InputFormat::from_matches(matches)?;
+    let given = |name: &str| -> Result<Option<f64>, failure::Error> {
+        match matches.value_of(name) {
+            Some(value) => Ok(Some(value.parse()?)),
+            None => Ok(None),
+        }
+    };
+    // Without the statistics or λ given ahead of time, every value is read
+    // before any is transformed.
+    let single_pass = if matches.is_present("zscore") {
+        match (given("mean")?, given("standard-deviation")?) {
+            (Some(mean), Some(standard_deviation)) => {
+                Some(Transform::z_score(mean, standard_deviation)?)
+            }
+            _ => None,
+        }
+    } else if matches.is_present("minmax") {
+        match (given("min")?, given("max")?) {
+            (Some(min), Some(max)) => Some(Transform::min_max(min, max)?),
+            _ => None,
+        }
+    } else if matches.is_present("log") {
+        Some(Transform::Log)
+    } else if matches.is_present("boxcox") {
+        given("boxcox")?.map(Transform::BoxCox)
+    } else {
+        given("yeo-johnson")?.map(Transform::YeoJohnson)
+    };
+    if let Some(transform) = single_pass {
+        for value in format.values(std::io::stdin().lock())? {
+            println!("{}", transform.apply(value?)?);
+        }
+        report_skipped(&format);
+        return Ok(());
+    }
+
+    let values = get_values_from_stdin(&format)?;
+    let mut summary = DistributionSummary::default();
+    summary.observe_many(values.iter())?;
+    let transform = if matches.is_present("zscore") {
+        Transform::z_score_of(&summary)?
+    } else if matches.is_present("minmax") {
+        Transform::min_max_of(&summary)?
+    } else if matches.is_present("boxcox") {
+        let lambda = transform::box_cox_lambda(&values)?;
+        eprintln!("Lambda: {}", lambda);
+        Transform::BoxCox(lambda)
+    } else {
+        let lambda = transform::yeo_johnson_lambda(&values)?;
+        eprintln!("Lambda: {}", lambda);
+        Transform::YeoJohnson(lambda)
+    };
+    for value in values {
+        println!("{}", transform.apply(value)?);
+    }
+    report_skipped(&format);
+    Ok(())
+}
+
+/// Evaluates an expression on each line read from stdin, printing either the
+/// result or, as a filter, the lines for which it is true. `x` is the value
+/// that other subcommands read, `$N` is the Nth column and any other variable
+/// is the column with that name in the header.
+fn evaluate_expression(matches: &ArgMatches, filter: bool) -> Result<(), failure::Error> {
+    let formula: expression::Formula = matches.value_of("expression").unwrap().parse()?;
+    let format = InputFormat::from_matches(matches)?;
+    let columns = formula
+        .variables
+        .iter()
+        .map(
+            |variable| match (variable.as_str(), variable.strip_prefix('$')) {
+                ("x", _) => Ok(format.column().cloned().unwrap_or(input::Column::Index(1))),
+                (_, Some(index))
+                    if !index.is_empty() && index.bytes().all(|b| b.is_ascii_digit()) =>
+                {
+                    index.parse()
+                }
+                (_, Some(_)) => Err(SamplersError::InvalidArgument {
+                    name: "expression".to_string(),
+                    value: format!("unknown column {}", variable),
+                }
+                .into()),
+                (name, None) => Ok(input::Column::Name(name.to_string())),
+            },
+        )
+        .collect::<Result<Vec<input::Column>, failure::Error>>()?;
+    let format = format.with_columns(columns);
+    let (header, lines) = format.lines_with_column_values(std::io::stdin().lock())?;
+    if let (Some(header), true) = (header, filter) {
+        println!("{}", header);
+    }
+    for line in lines {
+        let (line, values) = line?;
+        let result = formula.evaluate(&values);
+        if !filter {
+            println!("{}", result);
+        } else if expression::is_true(result) {
+            println!("{}", line);
+        }
+    }
+    report_skipped(&format);
+    Ok(())
+}
+
+fn distinct(matches: &ArgMatches) -> Result<(), failure::Error> {
+    let bits = clap::value_t!(matches, "bits", u32)?;
+    let mut sketch = sketch::HyperLogLog::with_precision(bits)?;
+    let format = InputFormat::from_matches(matches)?;
+    for key in format.keys(std::io::stdin().lock())? {
+        sketch.observe(&key?);
+    }
+    println!("{}", sketch.estimate().round());
+    report_skipped(&format);
+    Ok(())
+}
+
+fn top_k(matches: &ArgMatches) -> Result<(), failure::Error> {
+    let k = clap::value_t!(matches, "k", usize)?;
+    let capacity = if matches.is_present("capacity") {
+        clap::value_t!(matches, "capacity", usize)?
+    } else {
+        10 * k
+    };
+    let output_format = clap::value_t!(matches, "format", render::OutputFormat)?;
+    let mut sketch = sketch::SpaceSaving::with_capacity(capacity.max(k))?;
+    let format = InputFormat::from_matches(matches)?;
+    for key in format.keys(std::io::stdin().lock())? {
+        sketch.observe(&key?);
+    }
+    let rows: Vec<Vec<render::Cell>> = sketch
+        .top(k)
+        .into_iter()
+        .map(|(key, counter)| {
+            vec![
+                render::Cell::Text(key.to_string()),
+                render::Cell::Count(counter.count),
+                render::Cell::Count(counter.error),
+            ]
+        })
+        .collect();
+    render::render_table(
+        &["value", "count", "error"],
+        &rows,
+        output_format,
+        std::io::stdout(),
+    )?;
+    report_skipped(&format);
+    Ok(())
+}
+
+fn robust(matches: &ArgMatches, statistic: robust::RobustStatistic) -> Result<(), failure::Error> {
+    let trim = if statistic.is_trimmed() {
+        robust::validate_trim(clap::value_t!(matches, "trim", f64)? / 100.0)?
+    } else {
+        0.0
+    };
+    let format = InputFormat::from_matches(matches)?;
+    let mut values = get_values_from_stdin(&format)?;
+    println!(
+        "{}",
+        statistic
+            .evaluate(&mut values, trim)
+            .unwrap_or(std::f64::NAN)
+    );
+    report_skipped(&format);
+    Ok(())
+}
+
+fn outliers(matches: &ArgMatches) -> Result<(), failure::Error> {
+    let method = clap::value_t!(matches, "method", outliers::Method)?;
+    let threshold = if matches.is_present("threshold") {
+        clap::value_t!(matches, "threshold", f64)?
+    } else {
+        method.default_threshold()
+    };
+    let format = InputFormat::from_matches(matches)?;
+    let reader = open_input(matches.value_of("file"))?;
+    let (header, lines) = format.lines_with_values(reader)?;
+    let lines = lines.collect::<Result<Vec<(String, f64)>, failure::Error>>()?;
+    let values: Vec<f64> = lines.iter().map(|&(_, value)| value).collect();
+    let flags = outliers::outliers(&values, method, threshold)?;
+    let flag = matches.is_present("flag");
+    let separator = format.delimiter().unwrap_or('\t');
+    if let Some(header) = header {
+        if flag {
+            println!("{}{}outlier", header, separator);
+        } else {
+            println!("{}", header);
+        }
+    }
+    for ((line, _), is_outlier) in lines.iter().zip(flags) {
+        if flag {
+            println!("{}{}{}", line, separator, is_outlier as u8);
+        } else if !is_outlier {
+            println!("{}", line);
+        }
+    }
+    report_skipped(&format);
+    Ok(())
+}
+
+fn bootstrap(matches: &ArgMatches) -> Result<(), failure::Error> {
+    let statistic = clap::value_t!(matches, "stat", summary::Statistic)?;
+    let num_resamples = clap::value_t!(matches, "resamples", usize)?;
+    let level = inference::validate_confidence_level(clap::value_t!(matches, "ci", f64)?)?;
+    let format = InputFormat::from_matches(matches)?;
+    let values = get_values_from_stdin(&format)?;
+    let summary = bootstrap::bootstrap(&values, statistic, num_resamples, level, rng(matches)?)?;
+    println!("{}", summary);
+    report_skipped(&format);
+    Ok(())
+}
+
+fn compare(matches: &ArgMatches) -> Result<(), failure::Error> {
+    let alpha = clap::value_t!(matches, "alpha", f64)?;
+    let format = InputFormat::from_matches(matches)?;
+    let a = get_values_from_file(matches.value_of("first").unwrap(), &format)?;
+    let b = get_values_from_file(matches.value_of("second").unwrap(), &format)?;
+    println!("{}", compare::Comparison::new(&a, &b, alpha)?);
+    report_skipped(&format);
+    Ok(())
+}
+
+/// The binning given by `--bins`, or distinct values without it.
+fn binning(matches: &ArgMatches) -> Result<entropy::Binning, failure::Error> {
+    if matches.is_present("bins") {
+        Ok(entropy::Binning::Buckets(clap::value_t!(
+            matches, "bins", usize
+        )?))
+    } else {
+        Ok(entropy::Binning::Distinct)
+    }
+}
+
+fn entropy(matches: &ArgMatches) -> Result<(), failure::Error> {
+    let base = entropy::parse_base(matches.value_of("base").unwrap())?;
+    let format = InputFormat::from_matches(matches)?;
+    let records = get_records_from_stdin(&format)?;
+    let weights = entropy::outcome_weights(&[&records], binning(matches)?)?;
+    println!("{}", entropy::entropy(&weights[0], base)?);
+    report_skipped(&format);
+    Ok(())
+}
+
+fn divergence(matches: &ArgMatches) -> Result<(), failure::Error> {
+    let base = entropy::parse_base(matches.value_of("base").unwrap())?;
+    let format = InputFormat::from_matches(matches)?;
+    let a = get_records_from_file(matches.value_of("first").unwrap(), &format)?;
+    let b = get_records_from_file(matches.value_of("second").unwrap(), &format)?;
+    let smoothing = clap::value_t!(matches, "smoothing", f64)?;
+    if smoothing < 0.0 || smoothing.is_nan() {
+        return Err(SamplersError::InvalidArgument {
+            name: "smoothing".to_string(),
+            value: smoothing.to_string(),
+        }
+        .into());
+    }
+    let mut weights = entropy::outcome_weights(&[&a, &b], binning(matches)?)?;
+    println!(
+        "Entropy: {} vs {}",
+        entropy::entropy(&weights[0], base)?,
+        entropy::entropy(&weights[1], base)?
+    );
+    for weight in weights.iter_mut().flatten() {
+        *weight += smoothing;
+    }
+    let (p, q) = (&weights[0], &weights[1]);
+    println!("KL divergence: {}", entropy::kl_divergence(q, p, base)?);
+    println!(
+        "Reverse KL divergence: {}",
+        entropy::kl_divergence(p, q, base)?
+    );
+    println!(
+        "Jensen-Shannon distance: {}",
+        entropy::jensen_shannon_distance(p, q, base)?
+    );
+    report_skipped(&format);
+    Ok(())
+}
+
+fn gof(matches: &ArgMatches) -> Result<(), failure::Error> {
+    let distribution = clap::value_t!(matches, "dist", distributions::NamedDistribution)?;
+    let num_buckets = clap::value_t!(matches, "num-buckets", usize)?;
+    let format = InputFormat::from_matches(matches)?;
+    let mut values = get_values_from_stdin(&format)?;
+    println!(
+        "{}",
+        gof::GoodnessOfFit::new(&mut values, &distribution, num_buckets)?
+    );
+    report_skipped(&format);
+    Ok(())
+}
+
+fn fit(matches: &ArgMatches) -> Result<(), failure::Error> {
+    let format = InputFormat::from_matches(matches)?;
+    let values = get_values_from_stdin(&format)?;
+    let criterion = clap::value_t!(matches, "criterion", fit::Criterion)?;
+    match matches.value_of("dist") {
+        Some("auto") => {
+            // Rank every family that can describe the values, with discrete
+            // and continuous families ranked separately.
+            let rank = |families: &[fit::Family]| -> Vec<fit::Fit> {
+                let mut fits: Vec<fit::Fit> = families
+                    .iter()
+                    .filter_map(|&family| fit::fit(family, &values).ok())
+                    .filter(|fit| fit.criterion(criterion).is_finite())
+                    .collect();
+                fits.sort_by(|a, b| {
+                    a.criterion(criterion)
+                        .partial_cmp(&b.criterion(criterion))
+                        .unwrap_or(std::cmp::Ordering::Equal)
+                });
+                fits
+            };
+            let mut fits = rank(&fit::Family::DISCRETE);
+            fits.extend(rank(&fit::Family::CONTINUOUS));
+            if fits.is_empty() {
+                return Err(SamplersError::CouldNotFitDistribution {
+                    family: "auto".to_string(),
+                }
+                .into());
+            }
+            let fits: Vec<String> = fits.iter().map(|fit| fit.to_string()).collect();
+            println!("{}", fits.join("\n\n"));
+        }
+        _ => {
+            let family = clap::value_t!(matches, "dist", fit::Family)?;
+            println!("{}", fit::fit(family, &values)?);
+        }
+    }
+    report_skipped(&format);
+    Ok(())
+}
+
+fn normality(matches: &ArgMatches) -> Result<(), failure::Error> {
+    let format = InputFormat::from_matches(matches)?;
+    let mut values = get_values_from_stdin(&format)?;
+    println!("{}", normality::NormalityTests::new(&mut values)?);
+    report_skipped(&format);
+    Ok(())
+}
+
+fn correlate(matches: &ArgMatches) -> Result<(), failure::Error> {
+    let output_format = clap::value_t!(matches, "format", render::OutputFormat)?;
+    let format = InputFormat::from_matches(matches)?;
+    let reader = open_input(matches.value_of("file"))?;
+    let (names, rows) = format.column_values(reader)?;
+    // Ranks need every value, so the columns are buffered.
+    let mut columns: Vec<Vec<f64>> = vec![Vec::new(); names.len()];
+    for row in rows {
+        for (value, column) in row?.into_iter().zip(columns.iter_mut()) {
+            column.push(value);
+        }
+    }
+    let number = |value: Option<f64>| render::Cell::Number(value.unwrap_or(std::f64::NAN));
+    let test = |result: Option<inference::TestResult>| {
+        vec![
+            number(result.map(|result| result.statistic)),
+            number(result.map(|result| result.p_value)),
+        ]
+    };
+
+    if let Some(measure) = matches.value_of("matrix") {
+        // The matrix is symmetric, so only the upper triangle is computed.
+        let n = columns.len();
+        let mut matrix = vec![vec![None; n]; n];
+        for i in 0..n {
+            for j in i..n {
+                let correlation = correlate::Correlation::new(&columns[i], &columns[j])?;
+                let statistic =
+                    |result: Option<inference::TestResult>| result.map(|result| result.statistic);
+                let value = match measure {
+                    "covariance" => correlation.covariance,
+                    "pearson" => statistic(correlation.pearson),
+                    "spearman" => statistic(correlation.spearman),
+                    "kendall" => statistic(correlation.kendall),
+                    _ => unreachable!(),
+                };
+                matrix[i][j] = value;
+                matrix[j][i] = value;
+            }
+        }
+        let rows: Vec<Vec<render::Cell>> = names
+            .iter()
+            .zip(matrix)
+            .map(|(name, values)| {
+                std::iter::once(render::Cell::Text(name.clone()))
+                    .chain(values.into_iter().map(number))
+                    .collect()
+            })
+            .collect();
+        let mut header = vec!["column"];
+        header.extend(names.iter().map(String::as_str));
+        render::render_table(&header, &rows, output_format, std::io::stdout())?;
+        report_skipped(&format);
+        return Ok(());
+    }
+
+    let mut rows = Vec::new();
+    for i in 0..columns.len() {
+        for j in (i + 1)..columns.len() {
+            let correlation = correlate::Correlation::new(&columns[i], &columns[j])?;
+            let mut row = vec![
+                render::Cell::Text(names[i].clone()),
+                render::Cell::Text(names[j].clone()),
+                render::Cell::Count(correlation.count),
+                number(correlation.covariance),
+            ];
+            row.extend(test(correlation.pearson));
+            row.extend(test(correlation.spearman));
+            row.extend(test(correlation.kendall));
+            rows.push(row);
+        }
+    }
+    render::render_table(
+        &[
+            "x",
+            "y",
+            "count",
+            "covariance",
+            "pearson",
+            "pearson_p",
+            "spearman",
+            "spearman_p",
+            "kendall",
+            "kendall_p",
+        ],
+        &rows,
+        output_format,
+        std::io::stdout(),
+    )?;
+    report_skipped(&format);
+    Ok(())
+}
+
+fn regress(matches: &ArgMatches) -> Result<(), failure::Error> {
+    let degree = clap::value_t!(matches, "degree", usize)?;
+    let columns = vec![
+        clap::value_t!(matches, "x", input::Column)?,
+        clap::value_t!(matches, "y", input::Column)?,
+    ];
+    let format = InputFormat::from_matches(matches)?.with_columns(columns);
+    let reader = open_input(matches.value_of("file"))?;
+    let (header, lines) = format.lines_with_column_values(reader)?;
+    let lines = lines.collect::<Result<Vec<(String, Vec<f64>)>, failure::Error>>()?;
+    let xs: Vec<f64> = lines.iter().map(|(_, values)| values[0]).collect();
+    let ys: Vec<f64> = lines.iter().map(|(_, values)| values[1]).collect();
+    let regression = regress::Regression::fit(&xs, &ys, degree)?;
+    if !matches.is_present("residuals") {
+        println!("{}", regression);
+        report_skipped(&format);
+        return Ok(());
+    }
+    let separator = format.delimiter().unwrap_or('\t');
+    if let Some(header) = header {
+        println!("{}{}fitted{}residual", header, separator, separator);
+    }
+    let fits = regression.fitted().iter().zip(regression.residuals());
+    for ((line, _), (fitted, residual)) in lines.iter().zip(fits) {
+        println!("{}{}{}{}{}", line, separator, fitted, separator, residual);
+    }
+    report_skipped(&format);
+    Ok(())
+}
+
+fn rolling(matches: &ArgMatches) -> Result<(), failure::Error> {
+    let window = if matches.is_present("duration") {
+        rolling::Window::Duration(clap::value_t!(matches, "duration", f64)?)
+    } else {
+        rolling::Window::Count(clap::value_t!(matches, "window", usize)?)
+    };
+    let quantiles = match matches.values_of("quantile") {
+        Some(values) => values
+            .map(|value| value.parse::<f64>())
+            .collect::<Result<Vec<f64>, _>>()?,
+        None => Vec::new(),
+    };
+    let time_column = matches.value_of("time-column");
+    // Timestamped input has the time before the value by default.
+    let value_column = matches
+        .value_of("column")
+        .unwrap_or(if time_column.is_some() { "2" } else { "1" });
+    let mut columns = vec![value_column.parse()?];
+    if let Some(column) = time_column {
+        columns.insert(0, column.parse()?);
+    }
+    let format = InputFormat::from_matches(matches)?.with_columns(columns);
+    let mut exponential = match matches.value_of("half-life") {
+        Some(_) => Some(rolling::ExponentialSummary::new(clap::value_t!(
+            matches,
+            "half-life",
+            f64
+        )?)?),
+        None => None,
+    };
+    let mut summary = rolling::RollingSummary::new(window)?;
+
+    let reader = open_input(matches.value_of("file"))?;
+    let (_names, rows) = format.column_values(reader)?;
+    let mut header: Vec<String> = Vec::new();
+    if time_column.is_some() {
+        header.push("time".to_string());
+    }
+    header.extend(
+        ["value", "count", "mean", "stddev", "min", "max"]
+            .iter()
+            .map(|name| name.to_string()),
+    );
+    header.extend(quantiles.iter().map(|p| format!("p{}", p * 100.0)));
+    if exponential.is_some() {
+        header.extend(vec!["ewma".to_string(), "ewmvar".to_string()]);
+    }
+    // Each line is printed as soon as its value is read, so that a live
+    // stream can be followed. `render::render_table` aligns columns over
+    // every row, which would hold the output back until the input ends.
+    println!("{}", header.join("\t"));
+    for (i, row) in rows.enumerate() {
+        let row = row?;
+        // Without timestamps, each value is one unit of time after the last.
+        let (time, value) = match row[..] {
+            [time, value] => (time, value),
+            _ => (i as f64, row[0]),
+        };
+        summary.observe_at(time, value)?;
+        let mut fields: Vec<Option<f64>> = Vec::new();
+        if time_column.is_some() {
+            fields.push(Some(time));
+        }
+        fields.extend(vec![
+            Some(value),
+            Some(summary.count() as f64),
+            summary.mean(),
+            summary.standard_deviation(),
+            summary.min(),
+            summary.max(),
+        ]);
+        fields.extend(quantiles.iter().map(|&p| summary.quantile(p)));
+        if let Some(exponential) = exponential.as_mut() {
+            exponential.observe_at(time, value)?;
+            fields.push(exponential.mean());
+            fields.push(exponential.variance());
+        }
+        let fields: Vec<String> = fields
+            .into_iter()
+            .map(|field| field.unwrap_or(std::f64::NAN).to_string())
+            .collect();
+        println!("{}", fields.join("\t"));
+    }
+    report_skipped(&format);
     Ok(())
 }
 
-fn get_values_from_stdin() -> Result<Vec<f64>, failure::Error> {
-    let mut stdin = std::io::stdin();
-    let results = get_results_from_stdin(&mut stdin);
-    results.collect::<Result<Vec<f64>, failure::Error>>()
+fn summary_from_stdin(
+    format: &InputFormat,
+    precision: Precision,
+) -> Result<DistributionSummary, failure::Error> {
+    let mut summary = DistributionSummary::with_weights(format.weights().unwrap_or_default())
+        .with_precision(precision);
+    for record in format.records(std::io::stdin().lock())? {
+        let (value, weight) = record?;
+        summary.observe_weighted(&value, weight)?;
+    }
+    Ok(summary)
+}
+
+/// Reports the lines skipped while reading input, once the output that they
+/// were left out of has been written.
+fn report_skipped(format: &InputFormat) {
+    if let Some(report) = format.skipped().report() {
+        eprintln!("{}", report);
+    }
+}
+
+/// Reads the file at the path, or stdin without one.
+fn open_input(path: Option<&str>) -> Result<Box<dyn std::io::BufRead>, failure::Error> {
+    Ok(match path {
+        Some(path) => Box::new(std::io::BufReader::new(std::fs::File::open(path)?)),
+        None => Box::new(std::io::stdin().lock()),
+    })
+}
+
+fn get_values_from_file(path: &str, format: &InputFormat) -> Result<Vec<f64>, failure::Error> {
+    let file = std::io::BufReader::new(std::fs::File::open(path)?);
+    format.values(file)?.collect()
 }
 
-fn get_results_from_stdin(
-    stdin: &mut std::io::Stdin,
-) -> impl Iterator<Item = Result<f64, failure::Error>> + '_ {
-    stdin.lock().lines().map(|line| Ok(line?.parse::<f64>()?))
+fn get_records_from_file(
+    path: &str,
+    format: &InputFormat,
+) -> Result<Vec<(f64, f64)>, failure::Error> {
+    let file = std::io::BufReader::new(std::fs::File::open(path)?);
+    format.records(file)?.collect()
+}
+
+fn get_values_from_stdin(format: &InputFormat) -> Result<Vec<f64>, failure::Error> {
+    format.values(std::io::stdin().lock())?.collect()
+}
+
+fn get_records_from_stdin(format: &InputFormat) -> Result<Vec<(f64, f64)>, failure::Error> {
+    format.records(std::io::stdin().lock())?.collect()
 }
 
 fn main() -> Result<(), failure::Error> {
@@ -207,14 +1292,112 @@ fn main() -> Result<(), failure::Error> {
         .default_value("1")
         .takes_value(true);
 
-    let app_matches = App::new("samplers")
-        .about(
-            "Sample from common distributions and calculate summary statistics from the command \
-             line.",
+    let weights = Arg::with_name("weights")
+        .short("w")
+        .long("weights")
+        .help(
+            "Read a weight after each value, in the next field. Frequency weights count \
+             repeated values, while reliability weights give the relative importance of values.",
+        )
+        .possible_values(&["frequency", "reliability"])
+        .takes_value(true);
+
+    let weight_column = Arg::with_name("weight-column")
+        .long("weight-column")
+        .help("The column holding weights, by 1-based index or by name in the header.")
+        .takes_value(true);
+
+    let precision = Arg::with_name("precision")
+        .long("precision")
+        .help(
+            "How to accumulate sums. High precision compensates for rounding errors, which \
+             matters for long inputs with a large mean relative to their spread.",
+        )
+        .possible_values(&["standard", "high"])
+        .default_value("standard");
+
+    // The default is only set where `--trim` is optional, since clap treats
+    // a default value as given when checking that `--robust` is present.
+    let trim = Arg::with_name("trim")
+        .long("trim")
+        .help("The percentage of values to trim or winsorize at each end.")
+        .takes_value(true);
+
+    let bins = Arg::with_name("bins")
+        .short("b")
+        .long("bins")
+        .help("Group values into this many buckets between the smallest and largest value.")
+        .takes_value(true);
+
+    let base = Arg::with_name("base")
+        .long("base")
+        .help("The base of the logarithm: 2 for bits, e for nats or any other number.")
+        .default_value("2")
+        .takes_value(true);
+
+    let expression = Arg::with_name("expression").required(true).index(1);
+    let expression_help = "This reads from stdin. You can terminate stdin with CTRL+D.\nIn the \
+                           expression, x is the value that other subcommands read, $N is the Nth \
+                           column and any other name is a column named in the header. It may use \
+                           + - * / % ^, comparisons, && || !, the constants pi and e, and abs, \
+                           sqrt, exp, ln, log10, log2, sin, cos, tan, floor, ceil, round, pow, \
+                           min and max. Conditions are true unless 0 or NaN.";
+
+    let delimiter = Arg::with_name("delimiter")
+        .long("delimiter")
+        .help(
+            "The character separating fields, such as , or tab. By default, fields are \
+             separated by whitespace.",
+        )
+        .takes_value(true);
+
+    let header = Arg::with_name("header")
+        .long("header")
+        .help("Skip the first line, which names the columns.");
+
+    let on_invalid = Arg::with_name("on-invalid")
+        .long("on-invalid")
+        .help(
+            "What to do with a line that cannot be parsed: stop with an error, skip it, or skip \
+             it with a warning. The number of skipped lines is reported at the end.",
+        )
+        .possible_values(&["error", "skip", "warn"])
+        .default_value("error");
+
+    let nan = Arg::with_name("nan")
+        .long("nan")
+        .help("Whether to skip lines with NaN values or include them, making results NaN.")
+        .possible_values(&["skip", "propagate"])
+        .default_value("propagate");
+
+    // The options for reading values, shared by every subcommand that reads
+    // a single column.
+    let input = [
+        delimiter.clone(),
+        Arg::with_name("column")
+            .long("column")
+            .help("The column holding values, by 1-based index or by name in the header.")
+            .takes_value(true),
+        header.clone(),
+        on_invalid.clone(),
+        nan.clone(),
+    ];
+
+    let app_matches = App::new("samplers")
+        .about(
+            "Sample from common distributions and calculate summary statistics from the command \
+             line.",
         )
         .version("0.1.3")
         .set_term_width(0)
         .setting(AppSettings::SubcommandRequiredElseHelp)
+        .arg(
+            Arg::with_name("seed")
+                .long("seed")
+                .help("Seed the random number generator for reproducible output.")
+                .global(true)
+                .takes_value(true),
+        )
         .subcommand(
             SubCommand::with_name("gaussian")
                 .about("Sample from a normal distribution 𝓝（μ, σ²）")
@@ -263,6 +1446,49 @@ fn main() -> Result<(), failure::Error> {
                         .takes_value(true),
                 ),
         )
+        .subcommand(
+            SubCommand::with_name("gamma")
+                .about("Sample from a gamma distribution Gamma(k, β)")
+                .arg(num_experiments.clone())
+                .arg(
+                    Arg::with_name("shape")
+                        .short("k")
+                        .long("shape")
+                        .help("The shape of the gamma random variable, k.")
+                        .default_value("1.0")
+                        .takes_value(true),
+                )
+                .arg(
+                    Arg::with_name("rate")
+                        .short("r")
+                        .long("rate")
+                        .help("The rate of the gamma random variable, β.")
+                        .default_value("1.0")
+                        .takes_value(true),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("lognormal")
+                .about("Sample from a log-normal distribution Lognormal(μ, σ²)")
+                .arg(num_experiments.clone())
+                .arg(
+                    Arg::with_name("mean")
+                        .short("m")
+                        .long("mean")
+                        .help("The mean of the logarithm of the random variable, μ.")
+                        .default_value("0.0")
+                        .allow_hyphen_values(true)
+                        .takes_value(true),
+                )
+                .arg(
+                    Arg::with_name("variance")
+                        .short("v")
+                        .long("variance")
+                        .help("The variance of the logarithm of the random variable, σ².")
+                        .default_value("1.0")
+                        .takes_value(true),
+                ),
+        )
         .subcommand(
             SubCommand::with_name("uniform")
                 .about("Sample from a uniform distribution Uniform(a, b)")
@@ -325,8 +1551,117 @@ fn main() -> Result<(), failure::Error> {
                 .after_help(
                     "This reads from stdin. You can terminate stdin with CTRL+D.\nBy default, \
                      this command computes summary statistics in a single pass with a constant \
-                     amount of additional memory.",
-                ),
+                     amount of additional memory.\nIf a file is given with more than one thread, \
+                     the file is split into chunks that are summarized in parallel and merged.",
+                )
+                .arg(
+                    Arg::with_name("file")
+                        .help("A file to read values from instead of stdin.")
+                        .index(1),
+                )
+                .arg(
+                    Arg::with_name("threads")
+                        .short("j")
+                        .long("threads")
+                        .help(
+                            "The number of threads to use when summarizing a file. Invalid \
+                             lines are then located by their byte offset rather than their line \
+                             number, since a thread does not read the lines before its chunk.",
+                        )
+                        .default_value("1")
+                        .takes_value(true),
+                )
+                .arg(weights.clone())
+                .arg(weight_column.clone())
+                .arg(
+                    Arg::with_name("confidence")
+                        .short("c")
+                        .long("confidence")
+                        .help(
+                            "Also report confidence intervals at this level, e.g. 0.95: t-based \
+                             for the mean, chi-squared for the variance, and Wilson for the \
+                             proportion when every value is 0 or 1. With --format csv or json, \
+                             the bounds are columns such as mean_lower and mean_upper.",
+                        )
+                        .takes_value(true),
+                )
+                .arg(
+                    Arg::with_name("all-columns")
+                        .long("all-columns")
+                        .help(
+                            "Summarize every numeric column of the input as a row of a table. \
+                             Columns are named by the header, or by their index without one.",
+                        )
+                        .conflicts_with_all(&["column", "weights", "weight-column", "confidence"]),
+                )
+                .arg(
+                    Arg::with_name("group-by")
+                        .long("group-by")
+                        .help(
+                            "Summarize the values of each distinct key in this column, by \
+                             1-based index or by name in the header. Groups are printed as rows \
+                             of a table, ordered by key.",
+                        )
+                        .requires("column")
+                        .conflicts_with_all(&["all-columns", "confidence"])
+                        .takes_value(true),
+                )
+                .arg(
+                    Arg::with_name("sort-by")
+                        .long("sort-by")
+                        .help("Sort groups by this statistic in descending order.")
+                        .possible_values(&render::SUMMARY_STATISTICS)
+                        .requires("group-by")
+                        .takes_value(true),
+                )
+                .arg(
+                    Arg::with_name("top")
+                        .long("top")
+                        .help("Print only the first N groups.")
+                        .requires("group-by")
+                        .takes_value(true),
+                )
+                .arg(
+                    Arg::with_name("format")
+                        .long("format")
+                        .help("How to write the summary.")
+                        .possible_values(&["text", "csv", "json"])
+                        .default_value("text"),
+                )
+                .arg(
+                    Arg::with_name("every")
+                        .long("every")
+                        .help(
+                            "Also print the summary while reading, after every N lines or after \
+                             every duration such as 500ms, 10s, 5m or 1h.",
+                        )
+                        .conflicts_with_all(&["all-columns", "group-by"])
+                        .takes_value(true),
+                )
+                .arg(
+                    Arg::with_name("robust")
+                        .long("robust")
+                        .help(
+                            "Also calculate statistics that are insensitive to outliers, which \
+                             needs every value in memory.",
+                        )
+                        .conflicts_with_all(&[
+                            "all-columns",
+                            "group-by",
+                            "every",
+                            "weights",
+                            "weight-column",
+                        ]),
+                )
+                .arg(trim.clone().requires("robust"))
+                .arg(precision.clone())
+                .arg(
+                    Arg::with_name("reset")
+                        .long("reset")
+                        .help("Summarize only the values since the last summary was printed.")
+                        .requires("every"),
+                )
+                .args(&input),
         )
         .subcommand(
             SubCommand::with_name("histogram")
@@ -367,12 +1702,40 @@ fn main() -> Result<(), failure::Error> {
                         .help("The size of the histogram in the terminal.")
                         .default_value("80")
                         .takes_value(true),
-                ),
+                )
+                .arg(weights.clone())
+                .arg(weight_column.clone())
+                .args(&input),
         )
         .subcommand(
             SubCommand::with_name("mean")
                 .about("Calculate the mean of given values.")
-                .after_help("This reads from stdin. You can terminate stdin with CTRL+D."),
+                .after_help(
+                    "This reads from stdin. You can terminate stdin with CTRL+D.\nThe geometric, \
+                     harmonic and power means are only defined for positive values. The log mean \
+                     is ln(mean(exp(x))), for values that are logarithms.",
+                )
+                .arg(
+                    Arg::with_name("type")
+                        .short("t")
+                        .long("type")
+                        .help("Which mean to compute.")
+                        .possible_values(&["arithmetic", "geometric", "harmonic", "power", "log"])
+                        .default_value("arithmetic"),
+                )
+                .arg(
+                    Arg::with_name("exponent")
+                        .short("p")
+                        .long("exponent")
+                        .help("The exponent of the power mean, p.")
+                        .allow_hyphen_values(true)
+                        .required_if("type", "power")
+                        .takes_value(true),
+                )
+                .arg(precision.clone())
+                .arg(weights.clone())
+                .arg(weight_column.clone())
+                .args(&input),
         )
         .subcommand(
             SubCommand::with_name("variance")
@@ -385,7 +1748,620 @@ fn main() -> Result<(), failure::Error> {
                         .help("Whether to compute population variance or sample variance.")
                         .possible_values(&["population", "sample"])
                         .default_value("population"),
-                ),
+                )
+                .arg(precision.clone())
+                .arg(weights.clone())
+                .arg(weight_column.clone())
+                .args(&input),
+        )
+        .subcommand(
+            SubCommand::with_name("sum")
+                .about("Calculate the sum of given values.")
+                .after_help("This reads from stdin. You can terminate stdin with CTRL+D.")
+                .arg(precision.clone())
+                .args(&input),
+        )
+        .subcommand(
+            SubCommand::with_name("cumsum")
+                .about("Print the running sum of given values.")
+                .after_help(
+                    "This reads from stdin, printing each sum as its value is read. You can \
+                     terminate stdin with CTRL+D.",
+                )
+                .arg(precision)
+                .args(&input),
+        )
+        .subcommand(
+            SubCommand::with_name("cumprod")
+                .about("Print the running product of given values.")
+                .after_help(
+                    "This reads from stdin, printing each product as its value is read. You can \
+                     terminate stdin with CTRL+D.",
+                )
+                .args(&input),
+        )
+        .subcommand(
+            SubCommand::with_name("diff")
+                .about("Print the differences between given values and earlier values.")
+                .after_help(
+                    "This reads from stdin, printing each difference as its value is read. You \
+                     can terminate stdin with CTRL+D.\nThe first K values have no earlier value, \
+                     so K fewer differences are printed than values are read.",
+                )
+                .arg(
+                    Arg::with_name("lag")
+                        .short("k")
+                        .long("lag")
+                        .help("Subtract the value this many places earlier.")
+                        .default_value("1")
+                        .takes_value(true),
+                )
+                .args(&input),
+        )
+        .subcommand(
+            SubCommand::with_name("transform")
+                .about("Print given values after transforming each of them.")
+                .after_help(
+                    "This reads from stdin. You can terminate stdin with CTRL+D.\nThe z-score and \
+                     min-max transformations read every value before printing any, unless the \
+                     statistics they need are given. Without a λ, the Box-Cox and Yeo-Johnson \
+                     transformations estimate it by maximum likelihood, print it to stderr and \
+                     also read every value first.",
+                )
+                .arg(
+                    Arg::with_name("zscore")
+                        .long("zscore")
+                        .help("Subtract the mean and divide by the sample standard deviation."),
+                )
+                .arg(
+                    Arg::with_name("minmax")
+                        .long("minmax")
+                        .help("Rescale the minimum to 0 and the maximum to 1."),
+                )
+                .arg(
+                    Arg::with_name("log")
+                        .long("log")
+                        .help("Take the natural logarithm of positive values."),
+                )
+                .arg(
+                    Arg::with_name("boxcox")
+                        .long("boxcox")
+                        .value_name("lambda")
+                        .help("Apply the Box-Cox transformation to positive values.")
+                        .allow_hyphen_values(true)
+                        .takes_value(true)
+                        .min_values(0)
+                        .require_equals(true),
+                )
+                .arg(
+                    Arg::with_name("yeo-johnson")
+                        .long("yeo-johnson")
+                        .value_name("lambda")
+                        .help("Apply the Yeo-Johnson transformation, which allows any values.")
+                        .allow_hyphen_values(true)
+                        .takes_value(true)
+                        .min_values(0)
+                        .require_equals(true),
+                )
+                .group(
+                    ArgGroup::with_name("transformation")
+                        .args(&["zscore", "minmax", "log", "boxcox", "yeo-johnson"])
+                        .required(true),
+                )
+                .arg(
+                    Arg::with_name("mean")
+                        .long("mean")
+                        .help("The mean for the z-score, instead of the mean of the values.")
+                        .allow_hyphen_values(true)
+                        .requires_all(&["zscore", "standard-deviation"])
+                        .takes_value(true),
+                )
+                .arg(
+                    Arg::with_name("standard-deviation")
+                        .long("standard-deviation")
+                        .help("The standard deviation for the z-score.")
+                        .requires_all(&["zscore", "mean"])
+                        .takes_value(true),
+                )
+                .arg(
+                    Arg::with_name("min")
+                        .long("min")
+                        .help("The value mapped to 0, instead of the minimum of the values.")
+                        .allow_hyphen_values(true)
+                        .requires_all(&["minmax", "max"])
+                        .takes_value(true),
+                )
+                .arg(
+                    Arg::with_name("max")
+                        .long("max")
+                        .help("The value mapped to 1, instead of the maximum of the values.")
+                        .allow_hyphen_values(true)
+                        .requires_all(&["minmax", "min"])
+                        .takes_value(true),
+                )
+                .args(&input),
+        )
+        .subcommand(
+            SubCommand::with_name("map")
+                .about("Print the result of an expression for each line of input.")
+                .after_help(expression_help)
+                .arg(expression.clone().help("The expression to evaluate, such as 'x * 1000 + 3'."))
+                .args(&input),
+        )
+        .subcommand(
+            SubCommand::with_name("filter")
+                .about("Print the lines of input for which an expression is true.")
+                .after_help(expression_help)
+                .arg(expression.help("The condition to test, such as 'x > 0 && x < 10'."))
+                .args(&input),
+        )
+        .subcommand(
+            SubCommand::with_name("distinct")
+                .about("Estimate the number of distinct values.")
+                .after_help(
+                    "This reads from stdin. You can terminate stdin with CTRL+D.\nValues are \
+                     compared as text without surrounding whitespace and are not parsed as \
+                     numbers, so 1, 1.0 and 01 are three distinct values. This uses HyperLogLog, whose \
+                     memory is fixed by the number of bits: 2^bits bytes, for a relative \
+                     standard error of about 1.04 / sqrt(2^bits).",
+                )
+                .arg(
+                    Arg::with_name("bits")
+                        .short("b")
+                        .long("bits")
+                        .help("The number of bits selecting a register, from 4 to 18.")
+                        .default_value("14")
+                        .takes_value(true),
+                )
+                .args(&input),
+        )
+        .subcommand(
+            SubCommand::with_name("top-k")
+                .about("Estimate the most frequent values and their counts.")
+                .after_help(
+                    "This reads from stdin. You can terminate stdin with CTRL+D.\nValues are \
+                     compared as text without surrounding whitespace and are not parsed as \
+                     numbers, so 1, 1.0 and 01 are three distinct values. This uses the Space-Saving \
+                     algorithm, which keeps a fixed number of counters. Each count may \
+                     overestimate the true count by at most its error, and any value making up \
+                     more than 1 / capacity of the input is always found.",
+                )
+                .arg(
+                    Arg::with_name("k")
+                        .short("k")
+                        .help("The number of values to report.")
+                        .default_value("10")
+                        .takes_value(true),
+                )
+                .arg(
+                    Arg::with_name("capacity")
+                        .long("capacity")
+                        .help("The number of counters to keep, which is 10 times k by default.")
+                        .takes_value(true),
+                )
+                .arg(
+                    Arg::with_name("format")
+                        .long("format")
+                        .help("How to write the values.")
+                        .possible_values(&["text", "csv", "json"])
+                        .default_value("text"),
+                )
+                .args(&input),
+        )
+        .subcommand(
+            SubCommand::with_name("rank")
+                .about("Print the rank of each given value, from 1 for the smallest.")
+                .after_help(
+                    "This reads from stdin. You can terminate stdin with CTRL+D.\nTied values \
+                     are given the average of the ranks they span.",
+                )
+                .args(&input),
+        )
+        .subcommands(robust::RobustStatistic::ALL.iter().map(|statistic| {
+            let subcommand = SubCommand::with_name(statistic.name())
+                .about(statistic.description())
+                .after_help("This reads from stdin. You can terminate stdin with CTRL+D.");
+            if statistic.is_trimmed() {
+                subcommand
+                    .arg(trim.clone().default_value(DEFAULT_TRIM))
+                    .args(&input)
+            } else {
+                subcommand.args(&input)
+            }
+        }))
+        .subcommand(
+            SubCommand::with_name("bootstrap")
+                .about("Estimate a confidence interval for a statistic by resampling.")
+                .after_help(
+                    "This reads from stdin. You can terminate stdin with CTRL+D.\nThis reports \
+                     both percentile and bias-corrected and accelerated (BCa) bootstrap \
+                     intervals. Use --seed for reproducible intervals.",
+                )
+                .arg(
+                    Arg::with_name("stat")
+                        .short("s")
+                        .long("stat")
+                        .help(
+                            "The statistic to estimate: mean, median, variance, stddev, \
+                             skewness, kurtosis, or a percentile such as p90.",
+                        )
+                        .default_value("mean")
+                        .takes_value(true),
+                )
+                .arg(
+                    Arg::with_name("resamples")
+                        .short("r")
+                        .long("resamples")
+                        .help("The number of bootstrap resamples.")
+                        .default_value("10000")
+                        .takes_value(true),
+                )
+                .arg(
+                    Arg::with_name("ci")
+                        .short("c")
+                        .long("ci")
+                        .help("The confidence level of the intervals.")
+                        .default_value("0.95")
+                        .takes_value(true),
+                )
+                .args(&input),
+        )
+        .subcommand(
+            SubCommand::with_name("compare")
+                .about("Compare the values in two files.")
+                .after_help(
+                    "Differences are reported for the second file relative to the first. The \
+                     verdict is significant only if both Welch's t-test and the Mann-Whitney U \
+                     test reject at the given alpha and agree on the direction.",
+                )
+                .arg(
+                    Arg::with_name("first")
+                        .help("The file with the baseline values.")
+                        .required(true)
+                        .index(1),
+                )
+                .arg(
+                    Arg::with_name("second")
+                        .help("The file with the values to compare against the baseline.")
+                        .required(true)
+                        .index(2),
+                )
+                .arg(
+                    Arg::with_name("alpha")
+                        .short("a")
+                        .long("alpha")
+                        .help("The significance level of the verdict.")
+                        .default_value("0.05")
+                        .takes_value(true),
+                )
+                .args(&input),
+        )
+        .subcommand(
+            SubCommand::with_name("entropy")
+                .about("Calculate the Shannon entropy of given values.")
+                .after_help(
+                    "This reads from stdin. You can terminate stdin with CTRL+D.\nEach distinct \
+                     value is an outcome, which suits discrete data. For continuous data, use \
+                     --bins to group values into buckets of equal width instead.",
+                )
+                .arg(bins.clone())
+                .arg(base.clone())
+                .arg(weights.clone())
+                .arg(weight_column.clone())
+                .args(&input),
+        )
+        .subcommand(
+            SubCommand::with_name("divergence")
+                .about("Measure how far the distribution of values in two files differ.")
+                .after_help(
+                    "This reports the entropy of each file, the KL divergence of the second \
+                     file from the first, its reverse and the Jensen-Shannon distance between \
+                     them. The KL divergence is infinite when the first file has no values for \
+                     an outcome of the second, while the Jensen-Shannon distance is symmetric \
+                     and at most 1 in base 2.\nEach distinct value is an outcome, which suits \
+                     discrete data. For continuous data, use --bins to group the values of \
+                     both files into the same buckets of equal width instead.",
+                )
+                .arg(
+                    Arg::with_name("first")
+                        .help("The file with the baseline values.")
+                        .required(true)
+                        .index(1),
+                )
+                .arg(
+                    Arg::with_name("second")
+                        .help("The file with the values to compare against the baseline.")
+                        .required(true)
+                        .index(2),
+                )
+                .arg(
+                    Arg::with_name("smoothing")
+                        .long("smoothing")
+                        .help(
+                            "Add this weight to every outcome of both files, which keeps the KL \
+                             divergence finite when an outcome is missing from one of them.",
+                        )
+                        .default_value("0")
+                        .takes_value(true),
+                )
+                .arg(bins)
+                .arg(base)
+                .arg(weights)
+                .arg(weight_column)
+                .args(&input),
+        )
+        .subcommand(
+            SubCommand::with_name("gof")
+                .about("Test whether given values follow a distribution.")
+                .after_help(
+                    "This reads from stdin. You can terminate stdin with CTRL+D.\nThis runs the \
+                     Kolmogorov-Smirnov, Anderson-Darling and chi-squared goodness-of-fit tests. \
+                     The chi-squared test bins values like the histogram subcommand, merging \
+                     adjacent bins until each expects at least 5 values.",
+                )
+                .arg(
+                    Arg::with_name("dist")
+                        .short("D")
+                        .long("dist")
+                        .help(
+                            "The hypothesized distribution, such as normal(0, 1), \
+                             exponential(1), uniform(0, 1), poisson(1), binomial(10, 0.5), \
+                             gamma(2, 1) or lognormal(0, 1). Parameters follow the sampling \
+                             subcommands.",
+                        )
+                        .required(true)
+                        .takes_value(true),
+                )
+                .arg(
+                    Arg::with_name("num-buckets")
+                        .short("b")
+                        .long("num-buckets")
+                        .help("The number of histogram buckets for the chi-squared test.")
+                        .default_value("15")
+                        .takes_value(true),
+                )
+                .args(&input),
+        )
+        .subcommand(
+            SubCommand::with_name("fit")
+                .about("Fit a distribution to given values by maximum likelihood.")
+                .after_help(
+                    "This reads from stdin. You can terminate stdin with CTRL+D.\nThe fitted \
+                     distribution is printed both for the gof subcommand and as the sampling \
+                     subcommand that generates it.\nWith --dist auto, every continuous family \
+                     that can describe the values is fitted and ranked by the criterion, AIC by \
+                     default, with both AIC and BIC reported for each fit. For non-negative \
+                     integer values, the Poisson fit is listed first; its likelihood is a \
+                     probability rather than a density, so it is not ranked against the \
+                     continuous families.",
+                )
+                .arg(
+                    Arg::with_name("dist")
+                        .short("D")
+                        .long("dist")
+                        .help("The family of distributions to fit.")
+                        .possible_values(&[
+                            "auto",
+                            "normal",
+                            "exponential",
+                            "uniform",
+                            "poisson",
+                            "gamma",
+                            "lognormal",
+                        ])
+                        .default_value("auto"),
+                )
+                .arg(
+                    Arg::with_name("criterion")
+                        .long("criterion")
+                        .help("The information criterion that ranks fits with --dist auto.")
+                        .possible_values(&["aic", "bic"])
+                        .default_value("aic"),
+                )
+                .args(&input),
+        )
+        .subcommand(
+            SubCommand::with_name("normality")
+                .about("Test whether given values follow some normal distribution.")
+                .after_help(
+                    "This reads from stdin. You can terminate stdin with CTRL+D.\nThis runs the \
+                     Jarque-Bera, D'Agostino K² and Shapiro-Wilk tests. D'Agostino K² requires \
+                     at least 8 values and Shapiro-Wilk requires between 3 and 5000 values.",
+                )
+                .args(&input),
+        )
+        .subcommand(
+            SubCommand::with_name("correlate")
+                .about("Measure the correlation between columns of given values.")
+                .after_help(
+                    "This reads from stdin. You can terminate stdin with CTRL+D.\nThis reports \
+                     the covariance and the Pearson, Spearman and Kendall tau-b correlations of \
+                     each pair of columns, with p-values for the hypothesis of no correlation.",
+                )
+                .arg(
+                    Arg::with_name("file")
+                        .help("A file to read values from instead of stdin.")
+                        .index(1),
+                )
+                .arg(
+                    Arg::with_name("columns")
+                        .long("columns")
+                        .help(
+                            "The columns to correlate, by 1-based index or by name in the header, \
+                             separated by commas.",
+                        )
+                        .required(true)
+                        .multiple(true)
+                        .use_delimiter(true)
+                        .min_values(2)
+                        .takes_value(true),
+                )
+                .arg(
+                    Arg::with_name("matrix")
+                        .long("matrix")
+                        .help("Print a matrix of one measure between every pair of columns.")
+                        .possible_values(&["covariance", "pearson", "spearman", "kendall"])
+                        .takes_value(true),
+                )
+                .arg(
+                    Arg::with_name("format")
+                        .long("format")
+                        .help("How to write the correlations.")
+                        .possible_values(&["text", "csv", "json"])
+                        .default_value("text"),
+                )
+                .arg(delimiter.clone())
+                .arg(header.clone())
+                .arg(on_invalid.clone())
+                .arg(nan.clone()),
+        )
+        .subcommand(
+            SubCommand::with_name("regress")
+                .about("Fit a polynomial to pairs of values by least squares.")
+                .after_help(
+                    "This reads from stdin. You can terminate stdin with CTRL+D.\nThis reports \
+                     each coefficient with its standard error and a t-test of whether it is \
+                     zero, along with R² and a summary of the residuals.",
+                )
+                .arg(
+                    Arg::with_name("file")
+                        .help("A file to read values from instead of stdin.")
+                        .index(1),
+                )
+                .arg(
+                    Arg::with_name("x")
+                        .long("x")
+                        .help("The column holding x, by 1-based index or by name in the header.")
+                        .default_value("1"),
+                )
+                .arg(
+                    Arg::with_name("y")
+                        .long("y")
+                        .help("The column holding y, by 1-based index or by name in the header.")
+                        .default_value("2"),
+                )
+                .arg(
+                    Arg::with_name("degree")
+                        .short("d")
+                        .long("degree")
+                        .help("The degree of the polynomial.")
+                        .default_value("1"),
+                )
+                .arg(
+                    Arg::with_name("residuals")
+                        .short("r")
+                        .long("residuals")
+                        .help(
+                            "Print each line with the fitted value and residual of its pair \
+                             appended as columns.",
+                        ),
+                )
+                .arg(delimiter)
+                .arg(header)
+                .arg(on_invalid)
+                .arg(nan),
+        )
+        .subcommand(
+            SubCommand::with_name("rolling")
+                .about("Calculate summary statistics over a sliding window of given values.")
+                .after_help(
+                    "This reads from stdin. You can terminate stdin with CTRL+D.\nFor each line \
+                     of input, this prints a line of tab-separated statistics of the window \
+                     ending at that line, after a header naming them. The window holds either \
+                     the last K values or the values of the last T seconds, for input with a \
+                     column of times in seconds.",
+                )
+                .arg(
+                    Arg::with_name("file")
+                        .help("A file to read values from instead of stdin.")
+                        .index(1),
+                )
+                .arg(
+                    Arg::with_name("window")
+                        .short("k")
+                        .long("window")
+                        .help("The number of values in the window.")
+                        .required_unless("duration")
+                        .conflicts_with("duration")
+                        .takes_value(true),
+                )
+                .arg(
+                    Arg::with_name("duration")
+                        .short("t")
+                        .long("duration")
+                        .help("The length of the window in seconds.")
+                        .requires("time-column")
+                        .takes_value(true),
+                )
+                .arg(
+                    Arg::with_name("time-column")
+                        .long("time-column")
+                        .help(
+                            "The column holding times in seconds, by 1-based index or by name in \
+                             the header. Values are then read from the second column by default.",
+                        )
+                        .takes_value(true),
+                )
+                .arg(
+                    Arg::with_name("quantile")
+                        .short("q")
+                        .long("quantile")
+                        .help("Also print these quantiles of the window, between 0 and 1.")
+                        .multiple(true)
+                        .use_delimiter(true)
+                        .number_of_values(1)
+                        .takes_value(true),
+                )
+                .arg(
+                    Arg::with_name("half-life")
+                        .long("half-life")
+                        .help(
+                            "Also print the exponentially weighted moving average and variance, \
+                             with this half-life in values, or in seconds with --time-column.",
+                        )
+                        .takes_value(true),
+                )
+                .args(&input),
+        )
+        .subcommand(
+            SubCommand::with_name("outliers")
+                .about("Remove or flag outliers in given values.")
+                .after_help(
+                    "This reads from stdin. You can terminate stdin with CTRL+D.\nLines are passed \
+                     through unchanged, except for outliers, which are dropped. With --flag, every \
+                     line is kept with a column appended that is 1 for outliers and 0 otherwise.\n\
+                     The default thresholds are 1.5 interquartile ranges beyond the quartiles for \
+                     iqr, 3 standard deviations for zscore, a modified z-score of 3.5 for mad and \
+                     a significance level of 0.05 for grubbs.",
+                )
+                .arg(
+                    Arg::with_name("file")
+                        .help("A file to read values from instead of stdin.")
+                        .index(1),
+                )
+                .arg(
+                    Arg::with_name("method")
+                        .short("m")
+                        .long("method")
+                        .help("How to detect outliers.")
+                        .possible_values(&["iqr", "zscore", "mad", "grubbs"])
+                        .default_value("iqr"),
+                )
+                .arg(
+                    Arg::with_name("threshold")
+                        .long("threshold")
+                        .help(
+                            "How far from the rest a value must be to be an outlier, or the \
+                             significance level for grubbs.",
+                        )
+                        .takes_value(true),
+                )
+                .arg(
+                    Arg::with_name("flag")
+                        .long("flag")
+                        .help("Keep outliers and append a column flagging them."),
+                )
+                .args(&input),
         )
         .get_matches();
 
@@ -405,12 +2381,38 @@ fn main() -> Result<(), failure::Error> {
         ("gaussian", Some(matches)) => gaussian(matches),
         ("poisson", Some(matches)) => poisson(matches),
         ("exponential", Some(matches)) => exponential(matches),
+        ("gamma", Some(matches)) => gamma(matches),
+        ("lognormal", Some(matches)) => lognormal(matches),
         ("uniform", Some(matches)) => uniform(matches),
         ("binomial", Some(matches)) => binomial(matches),
         ("summarize", Some(matches)) => summarize(matches, input_method),
         ("histogram", Some(matches)) => histogram(matches, output_method),
         ("mean", Some(matches)) => mean(matches, input_method),
         ("variance", Some(matches)) => variance(matches, input_method),
+        ("sum", Some(matches)) => sum(matches),
+        ("cumsum", Some(matches)) => cumsum(matches),
+        ("cumprod", Some(matches)) => cumprod(matches),
+        ("diff", Some(matches)) => diff(matches),
+        ("map", Some(matches)) => evaluate_expression(matches, false),
+        ("filter", Some(matches)) => evaluate_expression(matches, true),
+        ("distinct", Some(matches)) => distinct(matches),
+        ("top-k", Some(matches)) => top_k(matches),
+        ("rank", Some(matches)) => rank(matches),
+        ("transform", Some(matches)) => transform(matches),
+        ("bootstrap", Some(matches)) => bootstrap(matches),
+        ("compare", Some(matches)) => compare(matches),
+        ("entropy", Some(matches)) => entropy(matches),
+        ("divergence", Some(matches)) => divergence(matches),
+        ("gof", Some(matches)) => gof(matches),
+        ("fit", Some(matches)) => fit(matches),
+        ("normality", Some(matches)) => normality(matches),
+        ("correlate", Some(matches)) => correlate(matches),
+        ("regress", Some(matches)) => regress(matches),
+        ("rolling", Some(matches)) => rolling(matches),
+        ("outliers", Some(matches)) => outliers(matches),
+        (name, Some(matches)) if name.parse::<robust::RobustStatistic>().is_ok() => {
+            robust(matches, name.parse()?)
+        }
         _ => unreachable!(),
     }
 }
@@ -0,0 +1,267 @@
+use std::fmt;
+
+use crate::inference::{self, TestResult};
+use crate::summary::{DistributionSummary, Observer};
+
+// The range of sample sizes for which Royston's approximation to the
+// Shapiro–Wilk test is valid.
+const SHAPIRO_WILK_MIN_COUNT: usize = 3;
+const SHAPIRO_WILK_MAX_COUNT: usize = 5000;
+
+// The skewness test behind D'Agostino's K² requires at least this many values.
+const DAGOSTINO_MIN_COUNT: u64 = 8;
+
+/// Tests of whether values are drawn from some normal distribution.
+#[derive(Debug)]
+pub struct NormalityTests {
+    summary: DistributionSummary,
+    jarque_bera: Option<TestResult>,
+    dagostino: Option<TestResult>,
+    shapiro_wilk: Option<TestResult>,
+}
+
+impl NormalityTests {
+    pub fn new(values: &mut [f64]) -> Result<NormalityTests, failure::Error> {
+        let mut summary = DistributionSummary::default();
+        summary.observe_many(values.iter())?;
+        values.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+        Ok(NormalityTests {
+            jarque_bera: jarque_bera_test(&summary),
+            dagostino: dagostino_test(&summary),
+            shapiro_wilk: shapiro_wilk_test(values),
+            summary,
+        })
+    }
+}
+
+/// The Jarque–Bera test, based on the sample skewness and kurtosis. The
+/// statistic is asymptotically chi-squared with two degrees of freedom.
+pub fn jarque_bera_test(summary: &DistributionSummary) -> Option<TestResult> {
+    let n = summary.count() as f64;
+    let skewness = summary.population_skewness()?;
+    let excess_kurtosis = summary.population_kurtosis()? - 3.0;
+    let statistic = n / 6.0 * (skewness.powi(2) + excess_kurtosis.powi(2) / 4.0);
+    if !statistic.is_finite() {
+        return None;
+    }
+    Some(TestResult {
+        statistic,
+        p_value: (-statistic / 2.0).exp(),
+    })
+}
+
+/// D'Agostino and Pearson's K² omnibus test, which combines normalizing
+/// transformations of the sample skewness and kurtosis. K² is asymptotically
+/// chi-squared with two degrees of freedom.
+pub fn dagostino_test(summary: &DistributionSummary) -> Option<TestResult> {
+    if summary.count() < DAGOSTINO_MIN_COUNT {
+        return None;
+    }
+    let n = summary.count() as f64;
+
+    // D'Agostino's transformation of the skewness.
+    let y = summary.population_skewness()? * ((n + 1.0) * (n + 3.0) / (6.0 * (n - 2.0))).sqrt();
+    let beta2 = 3.0 * (n * n + 27.0 * n - 70.0) * (n + 1.0) * (n + 3.0)
+        / ((n - 2.0) * (n + 5.0) * (n + 7.0) * (n + 9.0));
+    let w2 = -1.0 + (2.0 * (beta2 - 1.0)).sqrt();
+    let delta = 1.0 / (0.5 * w2.ln()).sqrt();
+    let alpha = (2.0 / (w2 - 1.0)).sqrt();
+    let z_skewness = delta * (y / alpha).asinh();
+
+    // Anscombe and Glynn's transformation of the kurtosis.
+    let expected = 3.0 * (n - 1.0) / (n + 1.0);
+    let variance = 24.0 * n * (n - 2.0) * (n - 3.0) / ((n + 1.0).powi(2) * (n + 3.0) * (n + 5.0));
+    let x = (summary.population_kurtosis()? - expected) / variance.sqrt();
+    let sqrt_beta1 = 6.0 * (n * n - 5.0 * n + 2.0) / ((n + 7.0) * (n + 9.0))
+        * (6.0 * (n + 3.0) * (n + 5.0) / (n * (n - 2.0) * (n - 3.0))).sqrt();
+    let a = 6.0 + 8.0 / sqrt_beta1 * (2.0 / sqrt_beta1 + (1.0 + 4.0 / sqrt_beta1.powi(2)).sqrt());
+    let ratio = (1.0 - 2.0 / a) / (1.0 + x * (2.0 / (a - 4.0)).sqrt());
+    let z_kurtosis = (1.0 - 2.0 / (9.0 * a) - ratio.cbrt()) / (2.0 / (9.0 * a)).sqrt();
+
+    let statistic = z_skewness.powi(2) + z_kurtosis.powi(2);
+    if !statistic.is_finite() {
+        return None;
+    }
+    Some(TestResult {
+        statistic,
+        p_value: (-statistic / 2.0).exp(),
+    })
+}
+
+/// The Shapiro–Wilk test on sorted values, using the approximations of
+/// Royston, "Remark AS R94: A Remark on Algorithm AS 181" (1995).
+pub fn shapiro_wilk_test(sorted_values: &[f64]) -> Option<TestResult> {
+    let n = sorted_values.len();
+    if !(SHAPIRO_WILK_MIN_COUNT..=SHAPIRO_WILK_MAX_COUNT).contains(&n) {
+        return None;
+    }
+    let coefficients = shapiro_wilk_coefficients(n);
+    let mean = sorted_values.iter().sum::<f64>() / n as f64;
+    let sum_squares: f64 = sorted_values
+        .iter()
+        .map(|value| (value - mean).powi(2))
+        .sum();
+    if sum_squares <= 0.0 {
+        return None;
+    }
+    let numerator: f64 = coefficients
+        .iter()
+        .zip(sorted_values)
+        .map(|(a, value)| a * value)
+        .sum();
+    let statistic = (numerator * numerator / sum_squares).min(1.0);
+    Some(TestResult {
+        statistic,
+        p_value: shapiro_wilk_p_value(n, statistic),
+    })
+}
+
+fn shapiro_wilk_coefficients(n: usize) -> Vec<f64> {
+    if n == 3 {
+        return vec![
+            -std::f64::consts::FRAC_1_SQRT_2,
+            0.0,
+            std::f64::consts::FRAC_1_SQRT_2,
+        ];
+    }
+    let polynomial = |coefficients: &[f64], x: f64| {
+        coefficients
+            .iter()
+            .rev()
+            .fold(0.0, |accumulator, coefficient| {
+                accumulator * x + coefficient
+            })
+    };
+    let nf = n as f64;
+    let m: Vec<f64> = (1..=n)
+        .map(|i| inference::normal_quantile((i as f64 - 0.375) / (nf + 0.25)))
+        .collect();
+    let m_sum_squares: f64 = m.iter().map(|x| x * x).sum();
+    let u = 1.0 / nf.sqrt();
+    let a_n = m[n - 1] / m_sum_squares.sqrt()
+        + polynomial(
+            &[0.0, 0.221157, -0.147981, -2.071190, 4.434685, -2.706056],
+            u,
+        );
+    let mut a = vec![0.0; n];
+    if n > 5 {
+        let a_n1 = m[n - 2] / m_sum_squares.sqrt()
+            + polynomial(
+                &[0.0, 0.042981, -0.293762, -1.752461, 5.682633, -3.582633],
+                u,
+            );
+        let phi = (m_sum_squares - 2.0 * m[n - 1].powi(2) - 2.0 * m[n - 2].powi(2))
+            / (1.0 - 2.0 * a_n.powi(2) - 2.0 * a_n1.powi(2));
+        for i in 2..n - 2 {
+            a[i] = m[i] / phi.sqrt();
+        }
+        a[1] = -a_n1;
+        a[n - 2] = a_n1;
+    } else {
+        let phi = (m_sum_squares - 2.0 * m[n - 1].powi(2)) / (1.0 - 2.0 * a_n.powi(2));
+        for i in 1..n - 1 {
+            a[i] = m[i] / phi.sqrt();
+        }
+    }
+    a[0] = -a_n;
+    a[n - 1] = a_n;
+    a
+}
+
+fn shapiro_wilk_p_value(n: usize, w: f64) -> f64 {
+    let nf = n as f64;
+    if n == 3 {
+        let p = 6.0 / std::f64::consts::PI * (w.sqrt().asin() - 0.75_f64.sqrt().asin());
+        return p.clamp(0.0, 1.0);
+    }
+    let z = if n <= 11 {
+        let gamma = -2.273 + 0.459 * nf;
+        let mean = 0.5440 - 0.39978 * nf + 0.025054 * nf.powi(2) - 0.0006714 * nf.powi(3);
+        let sigma = (1.3822 - 0.77857 * nf + 0.062767 * nf.powi(2) - 0.0020322 * nf.powi(3)).exp();
+        let y = gamma - (1.0 - w).ln();
+        if y <= 0.0 {
+            return 0.0;
+        }
+        (-y.ln() - mean) / sigma
+    } else {
+        let log_n = nf.ln();
+        let mean = -1.5861 - 0.31082 * log_n - 0.083751 * log_n.powi(2) + 0.0038915 * log_n.powi(3);
+        let sigma = (-0.4803 - 0.082676 * log_n + 0.0030302 * log_n.powi(2)).exp();
+        ((1.0 - w).ln() - mean) / sigma
+    };
+    // Small values of W are evidence against normality, and they map to
+    // large values of z.
+    inference::normal_upper_tail_p_value(z)
+}
+
+impl fmt::Display for NormalityTests {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Count: {}", self.summary.count())?;
+        writeln!(
+            f,
+            "Population skewness: {}",
+            self.summary.population_skewness().unwrap_or(std::f64::NAN)
+        )?;
+        writeln!(
+            f,
+            "Population kurtosis: {}",
+            self.summary.population_kurtosis().unwrap_or(std::f64::NAN)
+        )?;
+        match self.jarque_bera {
+            Some(test) => writeln!(
+                f,
+                "Jarque-Bera: JB = {}, p = {}",
+                test.statistic, test.p_value
+            )?,
+            None => writeln!(f, "Jarque-Bera: NaN")?,
+        }
+        match self.dagostino {
+            Some(test) => writeln!(
+                f,
+                "D'Agostino K²: K² = {}, p = {}",
+                test.statistic, test.p_value
+            )?,
+            None => writeln!(f, "D'Agostino K²: NaN")?,
+        }
+        match self.shapiro_wilk {
+            Some(test) => write!(
+                f,
+                "Shapiro-Wilk: W = {}, p = {}",
+                test.statistic, test.p_value
+            ),
+            None => write!(f, "Shapiro-Wilk: NaN"),
+        }
+    }
+}
+
+#[test]
+fn test_normality() -> Result<(), failure::Error> {
+    fn approx_eq(a: f64, b: f64) -> bool {
+        const THRESHOLD: f64 = 0.0001;
+        (a - b).abs() < THRESHOLD
+    }
+
+    // The weights of 11 men from Shapiro and Wilk (1965).
+    let mut values = [
+        148.0, 154.0, 158.0, 160.0, 161.0, 162.0, 166.0, 170.0, 182.0, 195.0, 236.0,
+    ];
+    let tests = NormalityTests::new(&mut values)?;
+    let shapiro_wilk = tests.shapiro_wilk.unwrap();
+    // Matches R's shapiro.test.
+    assert!(approx_eq(shapiro_wilk.statistic, 0.788815));
+    assert!(approx_eq(shapiro_wilk.p_value, 0.006704));
+    // Matches scipy.stats.jarque_bera and scipy.stats.normaltest.
+    let jarque_bera = tests.jarque_bera.unwrap();
+    assert!(approx_eq(jarque_bera.statistic, 6.982848));
+    assert!(approx_eq(jarque_bera.p_value, 0.030457));
+    let dagostino = tests.dagostino.unwrap();
+    assert!(approx_eq(dagostino.statistic, 13.034263));
+    assert!(approx_eq(dagostino.p_value, 0.001478));
+
+    let mut values = [1.0, 2.0];
+    let tests = NormalityTests::new(&mut values)?;
+    assert_eq!(tests.shapiro_wilk, None);
+    assert_eq!(tests.dagostino, None);
+
+    Ok(())
+}
@@ -0,0 +1,185 @@
+use crate::inference;
+use crate::robust;
+use crate::summary::{self, DistributionSummary, Observer};
+use crate::SamplersError;
+
+/// A rule for deciding which values are outliers.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Method {
+    /// Values more than k interquartile ranges outside the quartiles, as in
+    /// Tukey's fences.
+    Iqr,
+    /// Values more than k sample standard deviations from the mean.
+    ZScore,
+    /// Values with a modified z-score, 0.6745 (x - median) / MAD, above k,
+    /// following Iglewicz and Hoaglin.
+    Mad,
+    /// Values rejected by repeated two-sided Grubbs tests at a significance
+    /// level, which assume that the other values are normally distributed.
+    Grubbs,
+}
+
+impl Method {
+    /// The default threshold k, or significance level for Grubbs' test.
+    pub fn default_threshold(&self) -> f64 {
+        match self {
+            Method::Iqr => 1.5,
+            Method::ZScore => 3.0,
+            Method::Mad => 3.5,
+            Method::Grubbs => 0.05,
+        }
+    }
+}
+
+impl std::str::FromStr for Method {
+    type Err = failure::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "iqr" => Ok(Method::Iqr),
+            "zscore" => Ok(Method::ZScore),
+            "mad" => Ok(Method::Mad),
+            "grubbs" => Ok(Method::Grubbs),
+            _ => Err(SamplersError::InvalidArgument {
+                name: "outlier method".to_string(),
+                value: s.to_string(),
+            }
+            .into()),
+        }
+    }
+}
+
+/// Decides whether each value is an outlier, returning a flag for each value
+/// in order. Without enough values to estimate the spread, nothing is an
+/// outlier.
+pub fn outliers(
+    values: &[f64],
+    method: Method,
+    threshold: f64,
+) -> Result<Vec<bool>, failure::Error> {
+    if !(threshold > 0.0 && threshold.is_finite()) || (method == Method::Grubbs && threshold >= 1.0)
+    {
+        return Err(SamplersError::InvalidArgument {
+            name: "threshold".to_string(),
+            value: threshold.to_string(),
+        }
+        .into());
+    }
+    let mut sorted = values.to_vec();
+    sorted.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+    let is_outlier: Box<dyn Fn(f64) -> bool> = match method {
+        Method::Iqr => {
+            let (first, third) = match (
+                summary::quantile(&sorted, 0.25),
+                summary::quantile(&sorted, 0.75),
+            ) {
+                (Some(first), Some(third)) => (first, third),
+                _ => return Ok(vec![false; values.len()]),
+            };
+            let range = third - first;
+            Box::new(move |value| {
+                value < first - threshold * range || value > third + threshold * range
+            })
+        }
+        Method::ZScore => {
+            let mut summary = DistributionSummary::default();
+            summary.observe_many(values.iter())?;
+            match (summary.mean(), summary.standard_deviation()) {
+                (Some(mean), Some(deviation)) if deviation > 0.0 => {
+                    Box::new(move |value| ((value - mean) / deviation).abs() > threshold)
+                }
+                _ => return Ok(vec![false; values.len()]),
+            }
+        }
+        Method::Mad => {
+            const NORMAL_QUARTILE: f64 = 0.6745;
+            match (
+                summary::quantile(&sorted, 0.5),
+                robust::median_absolute_deviation(&sorted),
+            ) {
+                (Some(median), Some(deviation)) if deviation > 0.0 => Box::new(move |value| {
+                    (NORMAL_QUARTILE * (value - median) / deviation).abs() > threshold
+                }),
+                _ => return Ok(vec![false; values.len()]),
+            }
+        }
+        Method::Grubbs => return Ok(grubbs_outliers(values, threshold)),
+    };
+    Ok(values.iter().map(|&value| is_outlier(value)).collect())
+}
+
+/// Repeats the two-sided Grubbs test at the significance level, removing the
+/// value furthest from the mean while its statistic
+/// G = max |x - x̄| / s exceeds the critical value
+/// (n - 1) / √n √(t² / (n - 2 + t²)), where t is the upper α / (2n) quantile
+/// of Student's t distribution with n - 2 degrees of freedom.
+fn grubbs_outliers(values: &[f64], alpha: f64) -> Vec<bool> {
+    let mut flags = vec![false; values.len()];
+    let mut remaining: Vec<usize> = (0..values.len()).collect();
+    while remaining.len() > 2 {
+        let mut summary = DistributionSummary::default();
+        for &i in &remaining {
+            if summary.observe(&values[i]).is_err() {
+                return flags;
+            }
+        }
+        let (mean, deviation) = match (summary.mean(), summary.standard_deviation()) {
+            (Some(mean), Some(deviation)) if deviation > 0.0 => (mean, deviation),
+            _ => break,
+        };
+        let (position, furthest) = remaining
+            .iter()
+            .enumerate()
+            .map(|(position, &i)| (position, (values[i] - mean).abs()))
+            .fold((0, std::f64::NEG_INFINITY), |best, candidate| {
+                if candidate.1 > best.1 {
+                    candidate
+                } else {
+                    best
+                }
+            });
+        let n = remaining.len() as f64;
+        let t = match inference::students_t_quantile(1.0 - alpha / (2.0 * n), n - 2.0) {
+            Some(t) => t,
+            None => break,
+        };
+        let critical = (n - 1.0) / n.sqrt() * (t * t / (n - 2.0 + t * t)).sqrt();
+        if furthest / deviation <= critical {
+            break;
+        }
+        flags[remaining.remove(position)] = true;
+    }
+    flags
+}
+
+#[test]
+fn test_outliers() -> Result<(), failure::Error> {
+    let values = [
+        12.0, 10.5, 11.2, 9.8, 10.1, 250.0, 10.9, 11.5, 10.0, 9.6, 10.4, 180.0,
+    ];
+    let flagged = |method: Method, threshold: f64| -> Result<Vec<f64>, failure::Error> {
+        Ok(outliers(&values, method, threshold)?
+            .into_iter()
+            .zip(values.iter())
+            .filter(|(is_outlier, _)| *is_outlier)
+            .map(|(_, &value)| value)
+            .collect())
+    };
+    assert_eq!(flagged(Method::Iqr, 1.5)?, vec![250.0, 180.0]);
+    assert_eq!(flagged(Method::Mad, 3.5)?, vec![250.0, 180.0]);
+    // The outliers inflate the standard deviation enough to hide 180.
+    assert_eq!(flagged(Method::ZScore, 2.0)?, vec![250.0]);
+    // Removing 250 reveals 180 in the next round.
+    assert_eq!(flagged(Method::Grubbs, 0.05)?, vec![250.0, 180.0]);
+
+    // The critical value for ten values at the 5% level is 2.29.
+    let normal = [2.1, 1.9, 2.0, 2.3, 1.8, 2.2, 2.0, 1.9, 2.1, 2.4];
+    assert!(!outliers(&normal, Method::Grubbs, 0.05)?.contains(&true));
+    assert_eq!(
+        outliers(&[1.0, 1.0, 1.0], Method::ZScore, 3.0)?,
+        vec![false; 3]
+    );
+    assert!(outliers(&values, Method::Grubbs, 1.5).is_err());
+    assert!(outliers(&values, Method::Iqr, -1.0).is_err());
+    Ok(())
+}
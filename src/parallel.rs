@@ -0,0 +1,197 @@
+use std::fs::File;
+use std::io::{BufRead, BufReader, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+
+use crate::input::{InputFormat, InputPolicy, LineParser};
+use crate::summary::{DistributionSummary, Observer, Weights};
+use crate::summation::Precision;
+
+/// Summarizes a file by splitting it into `num_threads` contiguous byte ranges
+/// that are parsed and observed concurrently, then merged.
+///
+/// Each range owns the lines that begin inside it, so a line straddling a
+/// boundary is read by exactly one thread.
+pub fn summarize_file(
+    path: &Path,
+    num_threads: usize,
+    format: &InputFormat,
+    precision: Precision,
+) -> Result<DistributionSummary, failure::Error> {
+    let weights = format.weights().unwrap_or_default();
+    // The header, if any, is read up front and excluded from every range.
+    let mut header = String::new();
+    let offset = if format.has_header() {
+        BufReader::new(File::open(path)?).read_line(&mut header)? as u64
+    } else {
+        0
+    };
+    let parser = format.parser(Some(header.trim_end_matches(&['\n', '\r'][..])))?;
+    let len = std::fs::metadata(path)?.len();
+    let num_threads = num_threads.max(1) as u64;
+    let chunk_size = (len - offset) / num_threads + 1;
+    let handles: Vec<_> = (0..num_threads)
+        .map(|i| {
+            let path = path.to_path_buf();
+            let start = (offset + i * chunk_size).min(len);
+            let end = (offset + (i + 1) * chunk_size).min(len);
+            let parser = parser.clone();
+            let policy = format.policy();
+            std::thread::spawn(move || {
+                summarize_chunk(path, start, end, parser, policy, weights, precision)
+            })
+        })
+        .collect();
+
+    let mut summary = DistributionSummary::with_weights(weights).with_precision(precision);
+    for handle in handles {
+        let chunk_summary = handle
+            .join()
+            .map_err(|_| format_err!("a summarizing thread panicked"))??;
+        summary.merge(&chunk_summary);
+    }
+    Ok(summary)
+}
+
+fn summarize_chunk(
+    path: PathBuf,
+    start: u64,
+    end: u64,
+    parser: LineParser,
+    policy: InputPolicy,
+    weights: Weights,
+    precision: Precision,
+) -> Result<DistributionSummary, failure::Error> {
+    let mut reader = BufReader::new(File::open(path)?);
+    let mut position = start;
+    let mut line = String::new();
+    if start > 0 {
+        // Skip the remainder of a line owned by the previous chunk. Starting
+        // one byte early keeps a line that begins exactly at `start`.
+        reader.seek(SeekFrom::Start(start - 1))?;
+        position = start - 1 + reader.read_line(&mut line)? as u64;
+    }
+
+    let mut summary = DistributionSummary::with_weights(weights).with_precision(precision);
+    while position < end {
+        line.clear();
+        let num_bytes = reader.read_line(&mut line)?;
+        if num_bytes == 0 {
+            break;
+        }
+        // Line numbers are unknown without reading the preceding chunks, so
+        // lines are located by their byte offset instead.
+        let offset = position;
+        position += num_bytes as u64;
+        let line = line.trim_end_matches('\n').trim_end_matches('\r');
+        let record = policy.screen(
+            parser.parse(line),
+            || format!("the line at byte {}", offset),
+            |(value, _weight)| value.is_nan(),
+        );
+        if let Some(record) = record {
+            let (value, weight) = record?;
+            summary.observe_weighted(&value, weight)?;
+        }
+    }
+    Ok(summary)
+}
+
+#[test]
+fn test_summarize_file() -> Result<(), failure::Error> {
+    let directory = std::env::temp_dir().join(format!("samplers-parallel-{}", std::process::id()));
+    std::fs::create_dir_all(&directory)?;
+    let write = |name: &str, contents: &str| -> Result<PathBuf, failure::Error> {
+        let path = directory.join(name);
+        std::fs::write(&path, contents)?;
+        Ok(path)
+    };
+    let format = |args: &[&str]| -> Result<InputFormat, failure::Error> {
+        let matches = clap::App::new("test")
+            .arg(clap::Arg::with_name("header").long("header"))
+            .arg(
+                clap::Arg::with_name("column")
+                    .long("column")
+                    .takes_value(true),
+            )
+            .arg(
+                clap::Arg::with_name("delimiter")
+                    .long("delimiter")
+                    .takes_value(true),
+            )
+            .arg(
+                clap::Arg::with_name("on-invalid")
+                    .long("on-invalid")
+                    .takes_value(true),
+            )
+            .get_matches_from(std::iter::once("test").chain(args.iter().cloned()));
+        InputFormat::from_matches(&matches)
+    };
+    // Summarizing on any number of threads matches reading the file in a
+    // single pass.
+    let assert_matches_serial = |path: &Path, format: &InputFormat| -> Result<(), failure::Error> {
+        let mut serial = DistributionSummary::default();
+        for record in format.records(BufReader::new(File::open(path)?))? {
+            let (value, weight) = record?;
+            serial.observe_weighted(&value, weight)?;
+        }
+        let close = |a: Option<f64>, b: Option<f64>| match (a, b) {
+            (Some(a), Some(b)) => (a - b).abs() <= 1e-9 * b.abs().max(1.0),
+            (a, b) => a == b,
+        };
+        for num_threads in 1..=8 {
+            let parallel = summarize_file(path, num_threads, format, Precision::Standard)?;
+            assert_eq!(parallel.count(), serial.count(), "{} threads", num_threads);
+            assert_eq!(
+                (parallel.min(), parallel.max()),
+                (serial.min(), serial.max())
+            );
+            assert!(close(parallel.mean(), serial.mean()));
+            assert!(close(parallel.variance(), serial.variance()));
+        }
+        Ok(())
+    };
+
+    // Lines of different lengths straddle the chunk boundaries.
+    let values: String = (0..1000).map(|i| format!("{}\n", i * i % 997)).collect();
+    assert_matches_serial(&write("values", &values)?, &format(&[])?)?;
+
+    // The header is read once, rather than as a line of the first chunk.
+    let path = write(
+        "header",
+        &format!("name,value\n{}", values.replace('\n', ",1\n")),
+    )?;
+    let with_header = format(&["--delimiter", ",", "--column", "value"])?;
+    assert_matches_serial(&path, &with_header)?;
+    let summary = summarize_file(&path, 4, &with_header, Precision::Standard)?;
+    assert_eq!(summary.count(), 1000);
+
+    let crlf = values.replace('\n', "\r\n");
+    assert_matches_serial(&write("crlf", &crlf)?, &format(&[])?)?;
+
+    // Fewer bytes than threads, without a final newline.
+    assert_matches_serial(&write("short", "1\n2")?, &format(&[])?)?;
+
+    let path = write("empty", "")?;
+    assert_matches_serial(&path, &format(&[])?)?;
+    let summary = summarize_file(&path, 4, &format(&[])?, Precision::Standard)?;
+    assert_eq!(summary.count(), 0);
+
+    // Invalid lines follow the policy, and are located by their byte offset.
+    let path = write("invalid", "1\nx\n3\n")?;
+    let error = summarize_file(&path, 2, &format(&[])?, Precision::Standard)
+        .unwrap_err()
+        .to_string();
+    assert_eq!(
+        error,
+        "Could not parse the line at byte 2: Invalid value: x"
+    );
+    let skip = format(&["--on-invalid", "skip"])?;
+    assert_eq!(
+        summarize_file(&path, 2, &skip, Precision::Standard)?.count(),
+        2
+    );
+    assert_eq!(skip.skipped().invalid(), 1);
+
+    std::fs::remove_dir_all(&directory)?;
+    Ok(())
+}
@@ -0,0 +1,306 @@
+use std::fmt;
+
+use crate::inference::{self, TestResult};
+use crate::summary;
+use crate::SamplersError;
+
+/// An estimated coefficient of a regression.
+#[derive(Debug, Clone, Copy)]
+pub struct Coefficient {
+    pub estimate: f64,
+    pub standard_error: f64,
+    /// A t-test of whether the coefficient is zero.
+    pub test: Option<TestResult>,
+}
+
+/// A polynomial fitted to paired values by ordinary least squares.
+#[derive(Debug)]
+pub struct Regression {
+    count: usize,
+    /// The coefficients of x⁰, x¹, ..., in order.
+    coefficients: Vec<Coefficient>,
+    r_squared: f64,
+    adjusted_r_squared: f64,
+    residual_standard_error: f64,
+    degrees_of_freedom: f64,
+    fitted: Vec<f64>,
+    residuals: Vec<f64>,
+}
+
+impl Regression {
+    /// Fits y = b₀ + b₁x + ... + b_d x^d. The least squares problem is solved
+    /// with a QR decomposition of the design matrix after standardizing x,
+    /// which keeps it well conditioned, so the fitted values are accurate
+    /// even for large x. The coefficients are then converted back to powers
+    /// of x.
+    pub fn fit(xs: &[f64], ys: &[f64], degree: usize) -> Result<Regression, failure::Error> {
+        let n = xs.len().min(ys.len());
+        let num_coefficients = degree + 1;
+        if degree == 0 {
+            return Err(SamplersError::InvalidArgument {
+                name: "degree".to_string(),
+                value: degree.to_string(),
+            }
+            .into());
+        }
+        let could_not_fit = || SamplersError::CouldNotCalculateSummaryStatistic {
+            name: "regression coefficients".to_string(),
+        };
+        if n <= num_coefficients {
+            return Err(could_not_fit().into());
+        }
+        let (ys, xs) = (&ys[..n], &xs[..n]);
+        let center = summary::mean(xs.iter().cloned());
+        let (variance, _sample_variance) = summary::variance(xs.iter().cloned());
+        let scale = variance.sqrt();
+        if scale == 0.0 || !scale.is_finite() {
+            return Err(could_not_fit().into());
+        }
+        let standardized: Vec<f64> = xs.iter().map(|x| (x - center) / scale).collect();
+        let design: Vec<Vec<f64>> = (0..num_coefficients)
+            .map(|power| standardized.iter().map(|z| z.powi(power as i32)).collect())
+            .collect();
+        let (r, qty) = householder_qr(design, ys.to_vec()).ok_or_else(could_not_fit)?;
+        let r_inverse = invert_upper_triangular(&r);
+        let standardized_coefficients: Vec<f64> = (0..num_coefficients)
+            .map(|i| {
+                (i..num_coefficients)
+                    .map(|j| r_inverse[i][j] * qty[j])
+                    .sum()
+            })
+            .collect();
+
+        let fitted: Vec<f64> = standardized
+            .iter()
+            .map(|&z| evaluate(&standardized_coefficients, z))
+            .collect();
+        let residuals: Vec<f64> = ys.iter().zip(&fitted).map(|(y, f)| y - f).collect();
+        let residual_sum_of_squares: f64 = residuals.iter().map(|e| e * e).sum();
+        let mean_y = summary::mean(ys.iter().cloned());
+        let total_sum_of_squares: f64 = ys.iter().map(|y| (y - mean_y).powi(2)).sum();
+        let degrees_of_freedom = (n - num_coefficients) as f64;
+        let residual_variance = residual_sum_of_squares / degrees_of_freedom;
+        let r_squared = 1.0 - residual_sum_of_squares / total_sum_of_squares;
+
+        // The covariance of the standardized coefficients is σ² R⁻¹ R⁻ᵀ, and
+        // the coefficients of x are a linear transformation T of them, with
+        // covariance T R⁻¹ R⁻ᵀ Tᵀ σ².
+        let transform = unstandardize(center, scale, num_coefficients);
+        let transformed_r_inverse: Vec<Vec<f64>> = (0..num_coefficients)
+            .map(|i| {
+                (0..num_coefficients)
+                    .map(|j| {
+                        (0..num_coefficients)
+                            .map(|k| transform[i][k] * r_inverse[k][j])
+                            .sum()
+                    })
+                    .collect()
+            })
+            .collect();
+        let coefficients = (0..num_coefficients)
+            .map(|i| {
+                let estimate: f64 = (0..num_coefficients)
+                    .map(|k| transform[i][k] * standardized_coefficients[k])
+                    .sum();
+                let standard_error = (residual_variance
+                    * transformed_r_inverse[i].iter().map(|a| a * a).sum::<f64>())
+                .sqrt();
+                let test = inference::students_t_two_sided_p_value(
+                    estimate / standard_error,
+                    degrees_of_freedom,
+                )
+                .map(|p_value| TestResult {
+                    statistic: estimate / standard_error,
+                    p_value,
+                });
+                Coefficient {
+                    estimate,
+                    standard_error,
+                    test,
+                }
+            })
+            .collect();
+
+        Ok(Regression {
+            count: n,
+            coefficients,
+            r_squared,
+            adjusted_r_squared: 1.0 - (1.0 - r_squared) * (n as f64 - 1.0) / degrees_of_freedom,
+            residual_standard_error: residual_variance.sqrt(),
+            degrees_of_freedom,
+            fitted,
+            residuals,
+        })
+    }
+
+    /// The fitted value for each pair, in order.
+    pub fn fitted(&self) -> &[f64] {
+        &self.fitted
+    }
+
+    /// The difference between each observed and fitted value, in order.
+    pub fn residuals(&self) -> &[f64] {
+        &self.residuals
+    }
+}
+
+/// Evaluates a polynomial with Horner's method.
+fn evaluate(coefficients: &[f64], x: f64) -> f64 {
+    coefficients.iter().rev().fold(0.0, |sum, c| sum * x + c)
+}
+
+/// The matrix taking coefficients of powers of z = (x - center) / scale to
+/// coefficients of powers of x, by the binomial expansion of
+/// ((x - center) / scale)^j.
+fn unstandardize(center: f64, scale: f64, num_coefficients: usize) -> Vec<Vec<f64>> {
+    let mut transform = vec![vec![0.0; num_coefficients]; num_coefficients];
+    for j in 0..num_coefficients {
+        let mut binomial = 1.0;
+        for (k, row) in transform.iter_mut().enumerate().take(j + 1) {
+            row[j] = binomial * (-center).powi((j - k) as i32) / scale.powi(j as i32);
+            binomial *= (j - k) as f64 / (k + 1) as f64;
+        }
+    }
+    transform
+}
+
+/// Decomposes the matrix with the given columns into QR with Householder
+/// reflections, returning the upper triangular R and the first rows of Qᵀy.
+/// Returns `None` if the columns are linearly dependent.
+fn householder_qr(
+    mut columns: Vec<Vec<f64>>,
+    mut y: Vec<f64>,
+) -> Option<(Vec<Vec<f64>>, Vec<f64>)> {
+    let num_columns = columns.len();
+    for k in 0..num_columns {
+        let norm = columns[k][k..].iter().map(|a| a * a).sum::<f64>().sqrt();
+        let original_norm = columns[k].iter().map(|a| a * a).sum::<f64>().sqrt();
+        if norm <= 1e-10 * original_norm || norm == 0.0 {
+            return None;
+        }
+        let alpha = if columns[k][k] > 0.0 { -norm } else { norm };
+        let mut reflector = columns[k][k..].to_vec();
+        reflector[0] -= alpha;
+        let reflector_norm2: f64 = reflector.iter().map(|v| v * v).sum();
+        let reflect = |values: &mut [f64]| {
+            let dot: f64 = reflector
+                .iter()
+                .zip(values.iter())
+                .map(|(v, a)| v * a)
+                .sum();
+            for (a, v) in values.iter_mut().zip(&reflector) {
+                *a -= 2.0 * dot / reflector_norm2 * v;
+            }
+        };
+        for column in columns[k..].iter_mut() {
+            reflect(&mut column[k..]);
+        }
+        reflect(&mut y[k..]);
+    }
+    let r = (0..num_columns)
+        .map(|i| {
+            (0..num_columns)
+                .map(|j| if i <= j { columns[j][i] } else { 0.0 })
+                .collect()
+        })
+        .collect();
+    y.truncate(num_columns);
+    Some((r, y))
+}
+
+/// Inverts an upper triangular matrix with nonzero diagonal by back
+/// substitution.
+fn invert_upper_triangular(r: &[Vec<f64>]) -> Vec<Vec<f64>> {
+    let n = r.len();
+    let mut inverse = vec![vec![0.0; n]; n];
+    for j in 0..n {
+        inverse[j][j] = 1.0 / r[j][j];
+        for i in (0..j).rev() {
+            let sum: f64 = ((i + 1)..=j).map(|k| r[i][k] * inverse[k][j]).sum();
+            inverse[i][j] = -sum / r[i][i];
+        }
+    }
+    inverse
+}
+
+impl fmt::Display for Regression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Count: {}", self.count)?;
+        for (power, coefficient) in self.coefficients.iter().enumerate() {
+            let name = match power {
+                0 => "Intercept".to_string(),
+                1 => "x".to_string(),
+                _ => format!("x^{}", power),
+            };
+            let (t, p) = coefficient
+                .test
+                .map_or((std::f64::NAN, std::f64::NAN), |test| {
+                    (test.statistic, test.p_value)
+                });
+            writeln!(
+                f,
+                "{}: {} (standard error {}, t = {}, p = {})",
+                name, coefficient.estimate, coefficient.standard_error, t, p
+            )?;
+        }
+        writeln!(f, "R²: {}", self.r_squared)?;
+        writeln!(f, "Adjusted R²: {}", self.adjusted_r_squared)?;
+        writeln!(
+            f,
+            "Residual standard error: {} on {} degrees of freedom",
+            self.residual_standard_error, self.degrees_of_freedom
+        )?;
+        let mut residuals = self.residuals.clone();
+        residuals.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+        let quantile = |p: f64| summary::quantile(&residuals, p).unwrap_or(std::f64::NAN);
+        write!(
+            f,
+            "Residuals: minimum {}, first quartile {}, median {}, third quartile {}, maximum {}",
+            quantile(0.0),
+            quantile(0.25),
+            quantile(0.5),
+            quantile(0.75),
+            quantile(1.0)
+        )
+    }
+}
+
+#[test]
+fn test_regression() -> Result<(), failure::Error> {
+    fn approx_eq(a: f64, b: f64) -> bool {
+        const THRESHOLD: f64 = 0.0001;
+        (a - b).abs() < THRESHOLD
+    }
+
+    let xs = [1.0, 2.0, 3.0, 4.0, 5.0, 6.0];
+    let ys = [1.2, 1.9, 3.2, 3.8, 5.1, 6.3];
+    let linear = Regression::fit(&xs, &ys, 1)?;
+    // Matches lm(y ~ x) in R.
+    let [intercept, slope] = [linear.coefficients[0], linear.coefficients[1]];
+    assert!(approx_eq(intercept.estimate, 0.013333));
+    assert!(approx_eq(intercept.standard_error, 0.198214));
+    assert!(approx_eq(slope.estimate, 1.02));
+    assert!(approx_eq(slope.standard_error, 0.050897));
+    assert!(approx_eq(slope.test.unwrap().p_value, 0.000037));
+    assert!(approx_eq(linear.r_squared, 0.990139));
+    assert!(approx_eq(linear.residuals().iter().sum::<f64>(), 0.0));
+
+    // Matches lm(y ~ x + I(x^2)) in R, with x far from zero.
+    let shifted: Vec<f64> = xs.iter().map(|x| x + 1000.0).collect();
+    let quadratic = Regression::fit(&shifted, &ys, 2)?;
+    let coefficients = &quadratic.coefficients;
+    assert!(approx_eq(quadratic.r_squared, 0.994185));
+    assert!(approx_eq(coefficients[2].estimate, 0.044643));
+    assert!(approx_eq(coefficients[2].standard_error, 0.030899));
+    for (fitted, x) in quadratic.fitted().iter().zip(&shifted) {
+        let direct = coefficients[0].estimate
+            + coefficients[1].estimate * x
+            + coefficients[2].estimate * x * x;
+        assert!((fitted - direct).abs() < 1e-3);
+    }
+
+    assert!(Regression::fit(&xs[..2], &ys[..2], 1).is_err());
+    assert!(Regression::fit(&[1.0, 1.0, 1.0], &[1.0, 2.0, 3.0], 1).is_err());
+    assert!(Regression::fit(&xs, &ys, 0).is_err());
+    Ok(())
+}
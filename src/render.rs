@@ -1,6 +1,7 @@
 use std::io::Write;
 
 use crate::histogram::Bucket;
+use crate::summary::DistributionSummary;
 
 pub fn render_buckets(
     buckets: &[Bucket],
@@ -9,15 +10,19 @@ pub fn render_buckets(
 ) -> Result<(), failure::Error> {
     use itertools::{Itertools, Position};
 
-    let max_count = buckets
+    // Bars are proportional to the weight in each bucket, which is the count
+    // for unweighted values.
+    let max_weight = buckets
         .iter()
-        .map(|bucket| bucket.count())
-        .max()
+        .map(|bucket| bucket.weight())
+        .fold(None, |max: Option<f64>, weight| {
+            Some(max.map_or(weight, |max| max.max(weight)))
+        })
         .ok_or_else(|| format_err!("there are buckets"))?;
 
     for elem in buckets.iter().with_position() {
         let bucket = elem.into_inner();
-        let proportion: f64 = bucket.count() as f64 / max_count as f64;
+        let proportion: f64 = bucket.weight() / max_weight;
         let num_chars: f64 = display_size as f64 * proportion;
         writeln!(
             output,
@@ -26,7 +31,7 @@ pub fn render_buckets(
             format!("{}{}", "█".repeat(num_chars.floor() as usize), {
                 render_fraction_bar(num_chars.fract())
             }),
-            bucket.count(),
+            bucket.weight(),
         )?;
         match elem {
             Position::First(_) | Position::Middle(_) => {}
@@ -58,3 +63,341 @@ fn render_fraction_bar(frac: f64) -> &'static str {
         ""
     }
 }
+
+/// How tables of statistics are written.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum OutputFormat {
+    Text,
+    Csv,
+    Json,
+}
+
+impl std::str::FromStr for OutputFormat {
+    type Err = failure::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "text" => Ok(OutputFormat::Text),
+            "csv" => Ok(OutputFormat::Csv),
+            "json" => Ok(OutputFormat::Json),
+            _ => Err(crate::SamplersError::InvalidArgument {
+                name: "format".to_string(),
+                value: s.to_string(),
+            }
+            .into()),
+        }
+    }
+}
+
+/// The statistics reported for each row of a summary table.
+pub const SUMMARY_STATISTICS: [&str; 8] = [
+    "count", "min", "max", "mean", "variance", "stddev", "skewness", "kurtosis",
+];
+
+fn summary_statistics(summary: &DistributionSummary) -> [f64; 8] {
+    let nan = std::f64::NAN;
+    [
+        summary.count() as f64,
+        summary.min().unwrap_or(nan),
+        summary.max().unwrap_or(nan),
+        summary.mean().unwrap_or(nan),
+        summary.variance().unwrap_or(nan),
+        summary.standard_deviation().unwrap_or(nan),
+        summary.skewness().unwrap_or(nan),
+        summary.kurtosis().unwrap_or(nan),
+    ]
+}
+
+/// The statistic of the summary with the given name from
+/// `SUMMARY_STATISTICS`.
+pub fn summary_statistic(summary: &DistributionSummary, name: &str) -> Option<f64> {
+    let index = SUMMARY_STATISTICS
+        .iter()
+        .position(|&statistic| statistic == name)?;
+    Some(summary_statistics(summary)[index])
+}
+
+/// Sorts named summaries by a statistic from `SUMMARY_STATISTICS` in
+/// descending order, with NaN last, and keeps at most `top` of them.
+pub fn rank_summaries(
+    summaries: &mut Vec<(String, DistributionSummary)>,
+    sort_by: Option<&str>,
+    top: Option<usize>,
+) {
+    if let Some(statistic) = sort_by {
+        let key = |summary: &DistributionSummary| {
+            summary_statistic(summary, statistic).filter(|value| !value.is_nan())
+        };
+        summaries.sort_by(|(_, a), (_, b)| {
+            key(b)
+                .partial_cmp(&key(a))
+                .unwrap_or(std::cmp::Ordering::Equal)
+        });
+    }
+    if let Some(top) = top {
+        summaries.truncate(top);
+    }
+}
+
+/// Writes one row of statistics per named summary, like pandas' `describe()`
+/// transposed.
+pub fn render_summaries(
+    summaries: &[(String, DistributionSummary)],
+    format: OutputFormat,
+    output: impl Write,
+) -> Result<(), failure::Error> {
+    render_extended_summaries(summaries, &[], |_summary| Vec::new(), format, output)
+}
+
+/// Writes one row of statistics per named summary as `render_summaries`
+/// does, followed by the further statistics of each summary in the given
+/// columns.
+pub fn render_extended_summaries(
+    summaries: &[(String, DistributionSummary)],
+    extra_columns: &[&str],
+    extra: impl Fn(&DistributionSummary) -> Vec<f64>,
+    format: OutputFormat,
+    output: impl Write,
+) -> Result<(), failure::Error> {
+    let columns: Vec<&str> = std::iter::once("column")
+        .chain(SUMMARY_STATISTICS.iter().cloned())
+        .chain(extra_columns.iter().cloned())
+        .collect();
+    let rows: Vec<Vec<Cell>> = summaries
+        .iter()
+        .map(|(name, summary)| {
+            vec![Cell::Text(name.clone()), Cell::Count(summary.count())]
+                .into_iter()
+                .chain(
+                    summary_statistics(summary)[1..]
+                        .iter()
+                        .chain(&extra(summary))
+                        .map(|&value| Cell::Number(value)),
+                )
+                .collect()
+        })
+        .collect();
+    render_table(&columns, &rows, format, output)
+}
+
+/// A cell of a table.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Cell {
+    Text(String),
+    Count(u64),
+    Number(f64),
+}
+
+/// Writes a table with named columns. In the text format, numbers are
+/// rounded and columns are aligned; CSV is written with a header row, and
+/// JSON as an array with one object per row.
+pub fn render_table(
+    columns: &[&str],
+    rows: &[Vec<Cell>],
+    format: OutputFormat,
+    mut output: impl Write,
+) -> Result<(), failure::Error> {
+    match format {
+        OutputFormat::Text => {
+            // Columns of text are aligned to the left and numbers to the
+            // right.
+            let left_aligned: Vec<bool> = (0..columns.len())
+                .map(|i| matches!(rows.first().and_then(|row| row.get(i)), Some(Cell::Text(_))))
+                .collect();
+            let mut lines: Vec<Vec<String>> =
+                vec![columns.iter().map(|column| column.to_string()).collect()];
+            for row in rows {
+                lines.push(
+                    row.iter()
+                        .map(|cell| match cell {
+                            Cell::Text(text) => text.clone(),
+                            Cell::Count(count) => count.to_string(),
+                            Cell::Number(value) => format!("{:.6}", value),
+                        })
+                        .collect(),
+                );
+            }
+            let widths: Vec<usize> = (0..columns.len())
+                .map(|i| {
+                    lines
+                        .iter()
+                        .filter_map(|line| line.get(i))
+                        .map(|cell| cell.chars().count())
+                        .max()
+                        .unwrap_or(0)
+                })
+                .collect();
+            for line in lines {
+                let cells: Vec<String> = line
+                    .iter()
+                    .zip(widths.iter().zip(left_aligned.iter()))
+                    .map(|(cell, (&width, &left_aligned))| {
+                        if left_aligned {
+                            format!("{:<width$}", cell, width = width)
+                        } else {
+                            format!("{:>width$}", cell, width = width)
+                        }
+                    })
+                    .collect();
+                writeln!(output, "{}", cells.join("  ").trim_end())?;
+            }
+        }
+        OutputFormat::Csv => {
+            let header: Vec<String> = columns.iter().map(|column| csv_field(column)).collect();
+            writeln!(output, "{}", header.join(","))?;
+            for row in rows {
+                let cells: Vec<String> = row
+                    .iter()
+                    .map(|cell| match cell {
+                        Cell::Text(text) => csv_field(text),
+                        Cell::Count(count) => count.to_string(),
+                        Cell::Number(value) => value.to_string(),
+                    })
+                    .collect();
+                writeln!(output, "{}", cells.join(","))?;
+            }
+        }
+        OutputFormat::Json => {
+            let objects: Vec<String> = rows
+                .iter()
+                .map(|row| {
+                    let fields: Vec<String> = columns
+                        .iter()
+                        .zip(row.iter())
+                        .map(|(column, cell)| {
+                            let value = match cell {
+                                Cell::Text(text) => json_string(text),
+                                Cell::Count(count) => count.to_string(),
+                                Cell::Number(value) => json_number(*value),
+                            };
+                            format!("{}: {}", json_string(column), value)
+                        })
+                        .collect();
+                    format!("{{{}}}", fields.join(", "))
+                })
+                .collect();
+            writeln!(output, "[{}]", objects.join(", "))?;
+        }
+    }
+    Ok(())
+}
+
+fn csv_field(field: &str) -> String {
+    if field.contains(&[',', '"', '\n', '\r'][..]) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+fn json_string(s: &str) -> String {
+    let mut escaped = String::from("\"");
+    for c in s.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            c if c.is_control() => escaped.push_str(&format!("\\u{:04x}", c as u32)),
+            c => escaped.push(c),
+        }
+    }
+    escaped.push('"');
+    escaped
+}
+
+/// JSON has no representation of NaN or infinity, so they are written as null.
+fn json_number(value: f64) -> String {
+    if value.is_finite() {
+        value.to_string()
+    } else {
+        "null".to_string()
+    }
+}
+
+#[test]
+fn test_render_summaries() -> Result<(), failure::Error> {
+    use crate::summary::Observer;
+
+    let mut a = DistributionSummary::default();
+    a.observe_many([1.0, 2.0, 3.0].iter())?;
+    let mut b = DistributionSummary::default();
+    b.observe(&0.5)?;
+    let summaries = vec![("a".to_string(), a), ("b, \"c\"".to_string(), b)];
+    let render = |format| -> Result<String, failure::Error> {
+        let mut output = Vec::new();
+        render_summaries(&summaries, format, &mut output)?;
+        Ok(String::from_utf8(output)?)
+    };
+
+    assert_eq!(
+        render(OutputFormat::Csv)?,
+        "column,count,min,max,mean,variance,stddev,skewness,kurtosis\n\
+         a,3,1,3,2,1,1,0,NaN\n\
+         \"b, \"\"c\"\"\",1,0.5,0.5,0.5,NaN,NaN,NaN,NaN\n"
+    );
+    assert_eq!(
+        render(OutputFormat::Json)?,
+        "[{\"column\": \"a\", \"count\": 3, \"min\": 1, \"max\": 3, \"mean\": 2, \"variance\": 1, \
+         \"stddev\": 1, \"skewness\": 0, \"kurtosis\": null}, {\"column\": \"b, \\\"c\\\"\", \
+         \"count\": 1, \"min\": 0.5, \"max\": 0.5, \"mean\": 0.5, \"variance\": null, \"stddev\": \
+         null, \"skewness\": null, \"kurtosis\": null}]\n"
+    );
+    assert_eq!(
+        render(OutputFormat::Text)?,
+        "column  count       min       max      mean  variance    stddev  skewness  kurtosis\n\
+         a           3  1.000000  3.000000  2.000000  1.000000  1.000000  0.000000       NaN\n\
+         b, \"c\"      1  0.500000  0.500000  0.500000       NaN       NaN       NaN       NaN\n"
+    );
+
+    // Further statistics follow the others in each row.
+    let mut output = Vec::new();
+    render_extended_summaries(
+        &summaries[..1],
+        &["range"],
+        |summary| vec![summary.max().unwrap() - summary.min().unwrap()],
+        OutputFormat::Csv,
+        &mut output,
+    )?;
+    assert_eq!(
+        String::from_utf8(output)?,
+        "column,count,min,max,mean,variance,stddev,skewness,kurtosis,range\n\
+         a,3,1,3,2,1,1,0,NaN,2\n"
+    );
+    Ok(())
+}
+
+#[test]
+fn test_rank_summaries() -> Result<(), failure::Error> {
+    use crate::summary::Observer;
+
+    let summary = |values: &[f64]| -> Result<DistributionSummary, failure::Error> {
+        let mut summary = DistributionSummary::default();
+        summary.observe_many(values.iter())?;
+        Ok(summary)
+    };
+    let groups = vec![
+        ("a".to_string(), summary(&[1.0, 2.0])?),
+        ("b".to_string(), summary(&[5.0])?),
+        ("c".to_string(), summary(&[3.0, 4.0, 5.0])?),
+    ];
+    let names = |summaries: &[(String, DistributionSummary)]| -> Vec<String> {
+        summaries.iter().map(|(name, _)| name.clone()).collect()
+    };
+
+    let mut ranked = groups.clone();
+    rank_summaries(&mut ranked, Some("mean"), None);
+    assert_eq!(names(&ranked), vec!["b", "c", "a"]);
+
+    // A single value has no variance, so it sorts last.
+    let mut ranked = groups.clone();
+    rank_summaries(&mut ranked, Some("variance"), Some(2));
+    assert_eq!(names(&ranked), vec!["c", "a"]);
+
+    let mut ranked = groups.clone();
+    rank_summaries(&mut ranked, None, Some(1));
+    assert_eq!(names(&ranked), vec!["a"]);
+
+    let mut ranked = groups;
+    rank_summaries(&mut ranked, Some("count"), Some(5));
+    assert_eq!(names(&ranked), vec!["c", "a", "b"]);
+    Ok(())
+}
@@ -0,0 +1,312 @@
+use std::fmt;
+
+use crate::summary::{self, DistributionSummary, Observer};
+use crate::SamplersError;
+
+/// The tuning constant of the Huber M-estimate of location, in units of the
+/// scale. This matches `MASS::huber` in R.
+const HUBER_K: f64 = 1.5;
+
+/// Scales the median absolute deviation to a consistent estimate of the
+/// standard deviation of normally distributed values, rounded as in R.
+const MAD_NORMAL_SCALE: f64 = 1.4826;
+
+/// A statistic that is insensitive to a small number of outliers.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RobustStatistic {
+    MedianAbsoluteDeviation,
+    InterquartileRange,
+    TrimmedMean,
+    WinsorizedMean,
+    WinsorizedVariance,
+    Huber,
+}
+
+impl RobustStatistic {
+    pub const ALL: [RobustStatistic; 6] = [
+        RobustStatistic::MedianAbsoluteDeviation,
+        RobustStatistic::InterquartileRange,
+        RobustStatistic::TrimmedMean,
+        RobustStatistic::WinsorizedMean,
+        RobustStatistic::WinsorizedVariance,
+        RobustStatistic::Huber,
+    ];
+
+    /// The name of the subcommand that calculates the statistic.
+    pub fn name(&self) -> &'static str {
+        match self {
+            RobustStatistic::MedianAbsoluteDeviation => "mad",
+            RobustStatistic::InterquartileRange => "iqr",
+            RobustStatistic::TrimmedMean => "trimmed-mean",
+            RobustStatistic::WinsorizedMean => "winsorized-mean",
+            RobustStatistic::WinsorizedVariance => "winsorized-variance",
+            RobustStatistic::Huber => "huber",
+        }
+    }
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            RobustStatistic::MedianAbsoluteDeviation => {
+                "Calculate the median absolute deviation of given values."
+            }
+            RobustStatistic::InterquartileRange => {
+                "Calculate the interquartile range of given values."
+            }
+            RobustStatistic::TrimmedMean => "Calculate the trimmed mean of given values.",
+            RobustStatistic::WinsorizedMean => "Calculate the winsorized mean of given values.",
+            RobustStatistic::WinsorizedVariance => {
+                "Calculate the winsorized variance of given values."
+            }
+            RobustStatistic::Huber => "Calculate the Huber M-estimate of location of given values.",
+        }
+    }
+
+    /// Whether the statistic depends on the proportion of values trimmed from
+    /// each end.
+    pub fn is_trimmed(&self) -> bool {
+        matches!(
+            self,
+            RobustStatistic::TrimmedMean
+                | RobustStatistic::WinsorizedMean
+                | RobustStatistic::WinsorizedVariance
+        )
+    }
+
+    /// Evaluates the statistic over the values, which are sorted in place.
+    pub fn evaluate(&self, values: &mut [f64], trim: f64) -> Option<f64> {
+        sort(values);
+        match self {
+            RobustStatistic::MedianAbsoluteDeviation => median_absolute_deviation(values),
+            RobustStatistic::InterquartileRange => interquartile_range(values),
+            RobustStatistic::TrimmedMean => trimmed_mean(values, trim),
+            RobustStatistic::WinsorizedMean => winsorized(values, trim)?.mean(),
+            RobustStatistic::WinsorizedVariance => winsorized(values, trim)?.variance(),
+            RobustStatistic::Huber => huber_location(values),
+        }
+    }
+}
+
+impl std::str::FromStr for RobustStatistic {
+    type Err = failure::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        RobustStatistic::ALL
+            .iter()
+            .find(|statistic| statistic.name() == s)
+            .cloned()
+            .ok_or_else(|| {
+                SamplersError::InvalidArgument {
+                    name: "statistic".to_string(),
+                    value: s.to_string(),
+                }
+                .into()
+            })
+    }
+}
+
+/// Returns an error unless the proportion trimmed from each end is in
+/// [0, 0.5).
+pub fn validate_trim(trim: f64) -> Result<f64, failure::Error> {
+    if (0.0..0.5).contains(&trim) {
+        Ok(trim)
+    } else {
+        Err(SamplersError::InvalidArgument {
+            name: "trim".to_string(),
+            value: trim.to_string(),
+        }
+        .into())
+    }
+}
+
+fn sort(values: &mut [f64]) {
+    values.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+}
+
+/// The median of the absolute deviations from the median. This is not
+/// scaled to estimate the standard deviation.
+pub fn median_absolute_deviation(sorted_values: &[f64]) -> Option<f64> {
+    let median = summary::quantile(sorted_values, 0.5)?;
+    let mut deviations: Vec<f64> = sorted_values.iter().map(|v| (v - median).abs()).collect();
+    sort(&mut deviations);
+    summary::quantile(&deviations, 0.5)
+}
+
+pub fn interquartile_range(sorted_values: &[f64]) -> Option<f64> {
+    Some(summary::quantile(sorted_values, 0.75)? - summary::quantile(sorted_values, 0.25)?)
+}
+
+/// The number of values cut from each end, which is rounded down.
+fn num_trimmed(len: usize, trim: f64) -> usize {
+    (trim * len as f64).floor() as usize
+}
+
+/// The mean of the values left after removing the given proportion from each
+/// end. This matches `scipy.stats.trim_mean`.
+pub fn trimmed_mean(sorted_values: &[f64], trim: f64) -> Option<f64> {
+    let cut = num_trimmed(sorted_values.len(), trim);
+    let trimmed = &sorted_values[cut..sorted_values.len() - cut];
+    if trimmed.is_empty() {
+        return None;
+    }
+    Some(summary::mean(trimmed.iter().cloned()))
+}
+
+/// Summarizes the values after replacing the given proportion at each end
+/// with the nearest value that is kept.
+fn winsorized(sorted_values: &[f64], trim: f64) -> Option<DistributionSummary> {
+    let n = sorted_values.len();
+    let cut = num_trimmed(n, trim);
+    if n == 0 || 2 * cut >= n {
+        return None;
+    }
+    let (low, high) = (sorted_values[cut], sorted_values[n - 1 - cut]);
+    let mut summary = DistributionSummary::default();
+    for value in sorted_values {
+        summary.observe(&value.max(low).min(high)).ok()?;
+    }
+    Some(summary)
+}
+
+/// The Huber M-estimate of location, the root of Σ ψ((x - μ) / s) where ψ
+/// clips its argument to [-k, k] and s is the scaled median absolute
+/// deviation. This iterates the mean of the values clipped to μ ± ks,
+/// starting from the median, as `MASS::huber` does in R.
+pub fn huber_location(sorted_values: &[f64]) -> Option<f64> {
+    const MAX_ITERATIONS: usize = 1000;
+    let mut location = summary::quantile(sorted_values, 0.5)?;
+    let scale = MAD_NORMAL_SCALE * median_absolute_deviation(sorted_values)?;
+    if scale == 0.0 {
+        return Some(location);
+    }
+    for _ in 0..MAX_ITERATIONS {
+        let (low, high) = (location - HUBER_K * scale, location + HUBER_K * scale);
+        let next = summary::mean(sorted_values.iter().map(|value| value.max(low).min(high)));
+        let converged = (next - location).abs() < 1e-10 * scale;
+        location = next;
+        if converged {
+            break;
+        }
+    }
+    Some(location)
+}
+
+/// The robust statistics of a sample, for `summarize --robust`.
+#[derive(Debug)]
+pub struct RobustSummary {
+    trim: f64,
+    median: Option<f64>,
+    median_absolute_deviation: Option<f64>,
+    interquartile_range: Option<f64>,
+    trimmed_mean: Option<f64>,
+    winsorized_mean: Option<f64>,
+    winsorized_variance: Option<f64>,
+    huber: Option<f64>,
+}
+
+impl RobustSummary {
+    pub fn new(mut values: Vec<f64>, trim: f64) -> Result<RobustSummary, failure::Error> {
+        let trim = validate_trim(trim)?;
+        sort(&mut values);
+        let winsorized = winsorized(&values, trim);
+        Ok(RobustSummary {
+            trim,
+            median: summary::quantile(&values, 0.5),
+            median_absolute_deviation: median_absolute_deviation(&values),
+            interquartile_range: interquartile_range(&values),
+            trimmed_mean: trimmed_mean(&values, trim),
+            winsorized_mean: winsorized.as_ref().and_then(DistributionSummary::mean),
+            winsorized_variance: winsorized.as_ref().and_then(DistributionSummary::variance),
+            huber: huber_location(&values),
+        })
+    }
+
+    /// The names of the statistics in `statistics`, for tables.
+    pub const COLUMNS: [&'static str; 7] = [
+        "median",
+        "mad",
+        "iqr",
+        "trimmed_mean",
+        "winsorized_mean",
+        "winsorized_variance",
+        "huber",
+    ];
+
+    /// The statistics named by `COLUMNS`, which are NaN if undefined.
+    pub fn statistics(&self) -> [f64; 7] {
+        [
+            self.median,
+            self.median_absolute_deviation,
+            self.interquartile_range,
+            self.trimmed_mean,
+            self.winsorized_mean,
+            self.winsorized_variance,
+            self.huber,
+        ]
+        .map(|statistic| statistic.unwrap_or(std::f64::NAN))
+    }
+}
+
+impl fmt::Display for RobustSummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let percent = self.trim * 100.0;
+        write!(
+            f,
+            "Median: {}\nMedian absolute deviation: {}\nInterquartile range: {}\n{}% trimmed \
+             mean: {}\n{}% winsorized mean: {}\n{}% winsorized variance: {}\nHuber M-estimate: {}",
+            self.median.unwrap_or(std::f64::NAN),
+            self.median_absolute_deviation.unwrap_or(std::f64::NAN),
+            self.interquartile_range.unwrap_or(std::f64::NAN),
+            percent,
+            self.trimmed_mean.unwrap_or(std::f64::NAN),
+            percent,
+            self.winsorized_mean.unwrap_or(std::f64::NAN),
+            percent,
+            self.winsorized_variance.unwrap_or(std::f64::NAN),
+            self.huber.unwrap_or(std::f64::NAN),
+        )
+    }
+}
+
+#[test]
+fn test_robust_statistics() -> Result<(), failure::Error> {
+    fn approx_eq(a: Option<f64>, b: f64) -> bool {
+        const THRESHOLD: f64 = 0.0001;
+        (a.unwrap() - b).abs() < THRESHOLD
+    }
+
+    // Timings with two outliers.
+    let values = [
+        12.0, 10.5, 11.2, 9.8, 10.1, 250.0, 10.9, 11.5, 10.0, 9.6, 10.4, 180.0,
+    ];
+    let evaluate = |name: &str, trim: f64| -> Result<Option<f64>, failure::Error> {
+        Ok(name
+            .parse::<RobustStatistic>()?
+            .evaluate(&mut values.clone(), trim))
+    };
+    // Matches R's mad(x, constant = 1), IQR(x), mean(x, trim = 0.1),
+    // and MASS::huber(x)$mu.
+    assert!(approx_eq(evaluate("mad", 0.0)?, 0.75));
+    assert!(approx_eq(evaluate("iqr", 0.0)?, 1.55));
+    assert!(approx_eq(evaluate("trimmed-mean", 0.1)?, 27.64));
+    assert!(approx_eq(evaluate("winsorized-mean", 0.1)?, 38.85));
+    assert!(approx_eq(
+        evaluate("winsorized-variance", 0.1)?,
+        4347.375455
+    ));
+    assert!(approx_eq(evaluate("huber", 0.0)?, 10.933585));
+    // Trimming nothing leaves the mean.
+    assert!(approx_eq(
+        evaluate("trimmed-mean", 0.0)?,
+        summary::mean(values.iter().cloned())
+    ));
+
+    let robust = RobustSummary::new(values.to_vec(), 0.1)?;
+    assert_eq!(robust.statistics()[0], robust.median.unwrap());
+    assert!(approx_eq(Some(robust.statistics()[6]), 10.933585));
+
+    assert_eq!(huber_location(&[1.0, 1.0, 1.0, 5.0]), Some(1.0));
+    assert_eq!(trimmed_mean(&[], 0.1), None);
+    assert!(RobustSummary::new(values.to_vec(), 0.5).is_err());
+    assert!("mode".parse::<RobustStatistic>().is_err());
+    Ok(())
+}
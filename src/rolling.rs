@@ -0,0 +1,252 @@
+use std::collections::VecDeque;
+
+use crate::summary::{self, CentralMomentsSummary, Observer, Remover};
+use crate::SamplersError;
+
+/// Which of the most recent values are summarized.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Window {
+    /// The last K values.
+    Count(usize),
+    /// The values observed in the last T seconds, inclusive of the latest
+    /// time and exclusive of the time T seconds before it.
+    Duration(f64),
+}
+
+/// Summary statistics of a sliding window of values, updated as each value
+/// enters and leaves the window.
+///
+/// The central moments are updated in constant time, and the values in the
+/// window are also kept in sorted order for the minimum, maximum and
+/// quantiles.
+#[derive(Debug)]
+pub struct RollingSummary {
+    window: Window,
+    values: VecDeque<(f64, f64)>,
+    sorted: Vec<f64>,
+    moments: CentralMomentsSummary,
+}
+
+impl RollingSummary {
+    pub fn new(window: Window) -> Result<RollingSummary, failure::Error> {
+        let (name, value) = match window {
+            Window::Count(0) => ("window", "0".to_string()),
+            Window::Duration(duration) if !(duration > 0.0 && duration.is_finite()) => {
+                ("duration", duration.to_string())
+            }
+            _ => {
+                return Ok(RollingSummary {
+                    window,
+                    values: VecDeque::new(),
+                    sorted: Vec::new(),
+                    moments: CentralMomentsSummary::default(),
+                })
+            }
+        };
+        Err(SamplersError::InvalidArgument {
+            name: name.to_string(),
+            value,
+        }
+        .into())
+    }
+
+    /// Observes a value at the given time, then removes the values that have
+    /// left the window. Times must not decrease, and are ignored by windows of
+    /// a fixed count.
+    ///
+    /// Values and times must be finite: removing an infinite value cannot
+    /// undo its update to the moments, and a NaN time would never expire.
+    pub fn observe_at(&mut self, time: f64, value: f64) -> Result<(), failure::Error> {
+        if !value.is_finite() {
+            return Err(SamplersError::CouldNotObserveValue { value }.into());
+        }
+        if !time.is_finite() {
+            return Err(SamplersError::InvalidArgument {
+                name: "time".to_string(),
+                value: time.to_string(),
+            }
+            .into());
+        }
+        if matches!(self.values.back(), Some(&(last, _)) if time < last) {
+            return Err(SamplersError::InvalidArgument {
+                name: "time".to_string(),
+                value: format!("{} is earlier than the previous time", time),
+            }
+            .into());
+        }
+        self.moments.observe(&value)?;
+        let position = self.sorted.partition_point(|&v| v < value);
+        self.sorted.insert(position, value);
+        self.values.push_back((time, value));
+        while let Some(&(oldest_time, oldest)) = self.values.front() {
+            let expired = match self.window {
+                Window::Count(size) => self.values.len() > size,
+                Window::Duration(duration) => oldest_time <= time - duration,
+            };
+            if !expired {
+                break;
+            }
+            self.values.pop_front();
+            self.moments.remove(&oldest)?;
+            let position = self.sorted.partition_point(|&v| v < oldest);
+            self.sorted.remove(position);
+        }
+        Ok(())
+    }
+
+    pub fn count(&self) -> u64 {
+        self.moments.count()
+    }
+
+    pub fn mean(&self) -> Option<f64> {
+        self.moments.mean()
+    }
+
+    pub fn standard_deviation(&self) -> Option<f64> {
+        self.moments.standard_deviation()
+    }
+
+    pub fn min(&self) -> Option<f64> {
+        self.sorted.first().cloned()
+    }
+
+    pub fn max(&self) -> Option<f64> {
+        self.sorted.last().cloned()
+    }
+
+    pub fn quantile(&self, p: f64) -> Option<f64> {
+        summary::quantile(&self.sorted, p)
+    }
+}
+
+#[test]
+fn test_rolling_summary() -> Result<(), failure::Error> {
+    fn approx_eq(a: Option<f64>, b: f64) -> bool {
+        const THRESHOLD: f64 = 1e-9;
+        (a.unwrap() - b).abs() < THRESHOLD
+    }
+
+    let mut rolling = RollingSummary::new(Window::Count(3))?;
+    for (i, value) in [4.0, 1.0, 7.0, 2.0, 2.0].iter().enumerate() {
+        rolling.observe_at(i as f64, *value)?;
+    }
+    // The window holds 7, 2 and 2.
+    assert_eq!(rolling.count(), 3);
+    assert!(approx_eq(rolling.mean(), 11.0 / 3.0));
+    assert!(approx_eq(
+        rolling.standard_deviation(),
+        3.0f64.sqrt() * 5.0 / 3.0
+    ));
+    assert_eq!(rolling.min(), Some(2.0));
+    assert_eq!(rolling.max(), Some(7.0));
+    assert_eq!(rolling.quantile(0.5), Some(2.0));
+
+    let mut rolling = RollingSummary::new(Window::Duration(10.0))?;
+    rolling.observe_at(0.0, 5.0)?;
+    rolling.observe_at(4.0, 1.0)?;
+    rolling.observe_at(10.0, 3.0)?;
+    assert_eq!(rolling.count(), 2);
+    assert_eq!(rolling.min(), Some(1.0));
+    rolling.observe_at(30.0, 8.0)?;
+    assert_eq!(rolling.count(), 1);
+    assert!(approx_eq(rolling.mean(), 8.0));
+    assert!(rolling.standard_deviation().unwrap().is_nan());
+
+    assert!(rolling.observe_at(29.0, 1.0).is_err());
+    assert!(rolling.observe_at(31.0, std::f64::NAN).is_err());
+    assert!(rolling.observe_at(31.0, std::f64::INFINITY).is_err());
+    assert!(rolling.observe_at(std::f64::NAN, 1.0).is_err());
+    assert!(rolling.observe_at(std::f64::INFINITY, 1.0).is_err());
+    // Rejected values leave the window as it was.
+    assert_eq!(rolling.count(), 1);
+    assert!(approx_eq(rolling.mean(), 8.0));
+
+    assert!(RollingSummary::new(Window::Count(0)).is_err());
+    assert!(RollingSummary::new(Window::Duration(0.0)).is_err());
+    assert!(RollingSummary::new(Window::Duration(std::f64::NAN)).is_err());
+    Ok(())
+}
+
+/// The exponentially weighted moving average and variance, where the weight
+/// of each value halves after every half-life.
+///
+/// The mean and variance are weighted by the decayed weights themselves, so
+/// early values are not biased towards the first value. Uses the incremental
+/// update from Finch, "Incremental calculation of weighted mean and
+/// variance" (2009).
+#[derive(Debug)]
+pub struct ExponentialSummary {
+    half_life: f64,
+    last_time: Option<f64>,
+    weight_sum: f64,
+    mean: Option<f64>,
+    variance: f64,
+}
+
+impl ExponentialSummary {
+    pub fn new(half_life: f64) -> Result<ExponentialSummary, failure::Error> {
+        if !(half_life > 0.0 && half_life.is_finite()) {
+            return Err(SamplersError::InvalidArgument {
+                name: "half-life".to_string(),
+                value: half_life.to_string(),
+            }
+            .into());
+        }
+        Ok(ExponentialSummary {
+            half_life,
+            last_time: None,
+            weight_sum: 0.0,
+            mean: None,
+            variance: 0.0,
+        })
+    }
+
+    /// Observes a value at the given time. The weights of the previous values
+    /// decay by the time elapsed since the last value, so values observed at
+    /// the same time are weighted equally.
+    pub fn observe_at(&mut self, time: f64, value: f64) -> Result<(), failure::Error> {
+        if value.is_nan() {
+            return Err(SamplersError::CouldNotObserveValue { value }.into());
+        }
+        let elapsed = time - self.last_time.unwrap_or(time);
+        self.weight_sum = self.weight_sum * 0.5f64.powf(elapsed / self.half_life) + 1.0;
+        self.last_time = Some(time);
+        let alpha = 1.0 / self.weight_sum;
+        let delta = value - self.mean.unwrap_or(value);
+        self.mean = Some(self.mean.unwrap_or(value) + alpha * delta);
+        self.variance = (1.0 - alpha) * (self.variance + alpha * delta * delta);
+        Ok(())
+    }
+
+    pub fn mean(&self) -> Option<f64> {
+        self.mean
+    }
+
+    /// The weighted population variance.
+    pub fn variance(&self) -> Option<f64> {
+        self.mean.map(|_| self.variance)
+    }
+}
+
+#[test]
+fn test_exponential_summary() -> Result<(), failure::Error> {
+    fn approx_eq(a: Option<f64>, b: f64) -> bool {
+        const THRESHOLD: f64 = 1e-9;
+        (a.unwrap() - b).abs() < THRESHOLD
+    }
+
+    let mut summary = ExponentialSummary::new(1.0)?;
+    assert_eq!(summary.mean(), None);
+    summary.observe_at(0.0, 2.0)?;
+    assert!(approx_eq(summary.mean(), 2.0));
+    assert!(approx_eq(summary.variance(), 0.0));
+    // After one half-life, the first value has half the weight of the next.
+    summary.observe_at(1.0, 4.0)?;
+    assert!(approx_eq(summary.mean(), 10.0 / 3.0));
+    assert!(approx_eq(summary.variance(), 8.0 / 9.0));
+    summary.observe_at(1.0, 4.0)?;
+    assert!(approx_eq(summary.mean(), 3.6));
+
+    assert!(ExponentialSummary::new(0.0).is_err());
+    Ok(())
+}
@@ -0,0 +1,121 @@
+use std::collections::VecDeque;
+
+use crate::summation::{self, Precision};
+use crate::SamplersError;
+
+/// A value read from input, or the error from reading it.
+pub type Value = Result<f64, failure::Error>;
+
+/// The sum of the values. The sum of no values is 0. Without compensation,
+/// the values are summed pairwise, since no running sum is needed.
+pub fn sum(values: impl Iterator<Item = Value>, precision: Precision) -> Value {
+    match precision {
+        Precision::Standard => {
+            let mut sum = summation::PairwiseSum::default();
+            for value in values {
+                sum.add(value?);
+            }
+            Ok(sum.total())
+        }
+        Precision::High => cumulative_sums(values, precision).try_fold(0.0, |_total, sum| sum),
+    }
+}
+
+/// The running sum of the values, after each value.
+pub fn cumulative_sums(
+    values: impl Iterator<Item = Value>,
+    precision: Precision,
+) -> impl Iterator<Item = Value> {
+    values.scan((0.0, 0.0), move |(sum, compensation), value| {
+        Some(value.map(|value| {
+            match precision {
+                Precision::Standard => *sum += value,
+                Precision::High => summation::compensated_add(sum, compensation, value),
+            }
+            *sum + *compensation
+        }))
+    })
+}
+
+/// The running product of the values, after each value.
+pub fn cumulative_products(values: impl Iterator<Item = Value>) -> impl Iterator<Item = Value> {
+    values.scan(1.0, |product, value| {
+        Some(value.map(|value| {
+            *product *= value;
+            *product
+        }))
+    })
+}
+
+/// The difference between each value and the value `lag` places before it.
+/// The first `lag` values have nothing to subtract, so there are `lag` fewer
+/// differences than values.
+pub fn differences(
+    values: impl Iterator<Item = Value>,
+    lag: usize,
+) -> Result<impl Iterator<Item = Value>, failure::Error> {
+    if lag == 0 {
+        return Err(SamplersError::InvalidArgument {
+            name: "lag".to_string(),
+            value: lag.to_string(),
+        }
+        .into());
+    }
+    let mut previous = VecDeque::new();
+    Ok(values.filter_map(move |value| {
+        let value = match value {
+            Ok(value) => value,
+            Err(error) => return Some(Err(error)),
+        };
+        previous.push_back(value);
+        if previous.len() <= lag {
+            return None;
+        }
+        previous.pop_front().map(|lagged| Ok(value - lagged))
+    }))
+}
+
+#[test]
+fn test_series() -> Result<(), failure::Error> {
+    fn ok(values: &[f64]) -> impl Iterator<Item = Value> + '_ {
+        values.iter().map(|&value| Ok(value))
+    }
+    let collect = |values: &mut dyn Iterator<Item = Value>| values.collect::<Result<Vec<f64>, _>>();
+
+    let values = [3.0, -1.0, 4.0, 1.5, -5.0];
+    assert_eq!(sum(ok(&values), Precision::Standard)?, 2.5);
+    assert_eq!(sum(ok(&[]), Precision::Standard)?, 0.0);
+    assert_eq!(
+        collect(&mut cumulative_sums(ok(&values), Precision::Standard))?,
+        vec![3.0, 2.0, 6.0, 7.5, 2.5]
+    );
+    assert_eq!(
+        collect(&mut cumulative_products(ok(&values)))?,
+        vec![3.0, -3.0, -12.0, -18.0, 90.0]
+    );
+    assert_eq!(
+        collect(&mut differences(ok(&values), 1)?)?,
+        vec![-4.0, 5.0, -2.5, -6.5]
+    );
+    assert_eq!(
+        collect(&mut differences(ok(&values), 2)?)?,
+        vec![1.0, 2.5, -9.0]
+    );
+    assert!(collect(&mut differences(ok(&values), 5)?)?.is_empty());
+    // The buffer grows with the input rather than the lag.
+    assert!(collect(&mut differences(ok(&values), std::usize::MAX)?)?.is_empty());
+    assert!(differences(ok(&values), 0).is_err());
+
+    // Uncompensated sums lose the small values next to the large ones.
+    let cancelling = [1.0, 1e100, 1.0, -1e100];
+    assert_eq!(sum(ok(&cancelling), Precision::Standard)?, 0.0);
+    assert_eq!(
+        collect(&mut cumulative_sums(ok(&cancelling), Precision::Standard))?[3],
+        0.0
+    );
+    assert_eq!(sum(ok(&cancelling), Precision::High)?, 2.0);
+
+    let failing = vec![Ok(1.0), Err(format_err!("bad line")), Ok(2.0)];
+    assert!(sum(failing.into_iter(), Precision::Standard).is_err());
+    Ok(())
+}
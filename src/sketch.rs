@@ -0,0 +1,246 @@
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{BTreeSet, HashMap};
+use std::hash::{Hash, Hasher};
+use std::rc::Rc;
+
+use crate::SamplersError;
+
+/// A 64-bit hash of a key that is the same on every run.
+fn hash(key: &str) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    key.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Estimates the number of distinct keys in a stream with HyperLogLog, in
+/// memory fixed by its precision.
+///
+/// The first `precision` bits of the hash of each key pick one of
+/// 2^precision registers, which keeps the longest run of leading zeros seen in
+/// the remaining bits. The relative standard error is about
+/// 1.04 / sqrt(2^precision), so the default precision of 14 uses 16 KiB for
+/// an error of 0.8%.
+#[derive(Debug, Clone)]
+pub struct HyperLogLog {
+    precision: u32,
+    registers: Vec<u8>,
+}
+
+impl HyperLogLog {
+    pub const MIN_PRECISION: u32 = 4;
+    pub const MAX_PRECISION: u32 = 18;
+
+    pub fn with_precision(precision: u32) -> Result<HyperLogLog, failure::Error> {
+        if !(Self::MIN_PRECISION..=Self::MAX_PRECISION).contains(&precision) {
+            return Err(SamplersError::InvalidArgument {
+                name: "bits".to_string(),
+                value: precision.to_string(),
+            }
+            .into());
+        }
+        Ok(HyperLogLog {
+            precision,
+            registers: vec![0; 1 << precision],
+        })
+    }
+
+    pub fn observe(&mut self, key: &str) {
+        let hash = hash(key);
+        let index = (hash >> (64 - self.precision)) as usize;
+        // The rank is at most 64 - precision + 1, once every remaining bit
+        // is zero.
+        let rank = ((hash << self.precision).leading_zeros() + 1).min(64 - self.precision + 1);
+        self.registers[index] = self.registers[index].max(rank as u8);
+    }
+
+    /// The estimated number of distinct keys.
+    pub fn estimate(&self) -> f64 {
+        let m = self.registers.len() as f64;
+        let alpha = match self.registers.len() {
+            16 => 0.673,
+            32 => 0.697,
+            64 => 0.709,
+            _ => 0.7213 / (1.0 + 1.079 / m),
+        };
+        let sum: f64 = self
+            .registers
+            .iter()
+            .map(|&register| 2f64.powi(-(register as i32)))
+            .sum();
+        let estimate = alpha * m * m / sum;
+        // Few keys leave registers empty, where counting the empty registers
+        // is more accurate.
+        let empty = self
+            .registers
+            .iter()
+            .filter(|&&register| register == 0)
+            .count();
+        if estimate <= 2.5 * m && empty > 0 {
+            m * (m / empty as f64).ln()
+        } else {
+            estimate
+        }
+    }
+}
+
+/// The count of a key tracked by `SpaceSaving`. The true count is between
+/// `count - error` and `count`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Counter {
+    pub count: u64,
+    pub error: u64,
+}
+
+/// Finds the most frequent keys in a stream with the Space-Saving algorithm,
+/// keeping a fixed number of counters.
+///
+/// A new key takes over the counter with the smallest count when every
+/// counter is in use, inheriting that count as its error. Any key seen more
+/// than N / capacity times in N keys is guaranteed to have a counter.
+///
+/// The counters are also kept ordered by count, so that each key is observed
+/// in time logarithmic in the capacity.
+#[derive(Debug, Clone)]
+pub struct SpaceSaving {
+    capacity: usize,
+    counters: HashMap<Rc<str>, Counter>,
+    // Each key by its count, and then by the key itself so that ties are
+    // broken the same way on every run.
+    by_count: BTreeSet<(u64, Rc<str>)>,
+}
+
+impl SpaceSaving {
+    pub fn with_capacity(capacity: usize) -> Result<SpaceSaving, failure::Error> {
+        if capacity == 0 {
+            return Err(SamplersError::InvalidArgument {
+                name: "capacity".to_string(),
+                value: capacity.to_string(),
+            }
+            .into());
+        }
+        Ok(SpaceSaving {
+            capacity,
+            counters: HashMap::with_capacity(capacity),
+            by_count: BTreeSet::new(),
+        })
+    }
+
+    pub fn observe(&mut self, key: &str) {
+        if let Some((key, counter)) = self.counters.get_key_value(key) {
+            let key = Rc::clone(key);
+            self.by_count.remove(&(counter.count, Rc::clone(&key)));
+            self.by_count.insert((counter.count + 1, Rc::clone(&key)));
+            self.counters.get_mut(&key).unwrap().count += 1;
+            return;
+        }
+        let error = if self.counters.len() < self.capacity {
+            0
+        } else {
+            let (count, smallest) = self.by_count.iter().next().cloned().unwrap();
+            self.by_count.remove(&(count, Rc::clone(&smallest)));
+            self.counters.remove(&smallest);
+            count
+        };
+        let key: Rc<str> = Rc::from(key);
+        self.by_count.insert((error + 1, Rc::clone(&key)));
+        self.counters.insert(
+            key,
+            Counter {
+                count: error + 1,
+                error,
+            },
+        );
+    }
+
+    /// The `k` keys with the largest counts, from the largest.
+    pub fn top(&self, k: usize) -> Vec<(&str, Counter)> {
+        let mut counters: Vec<(&str, Counter)> = self
+            .counters
+            .iter()
+            .map(|(key, &counter)| (key.as_ref(), counter))
+            .collect();
+        counters.sort_by(|(a, x), (b, y)| y.count.cmp(&x.count).then_with(|| a.cmp(b)));
+        counters.truncate(k);
+        counters
+    }
+}
+
+#[test]
+fn test_hyperloglog() -> Result<(), failure::Error> {
+    let mut sketch = HyperLogLog::with_precision(14)?;
+    for i in 0..100_000 {
+        sketch.observe(&i.to_string());
+        sketch.observe(&i.to_string());
+    }
+    let error = (sketch.estimate() - 100_000.0).abs() / 100_000.0;
+    assert!(error < 3.0 * 1.04 / 2f64.powi(7), "{}", error);
+
+    let mut sketch = HyperLogLog::with_precision(14)?;
+    for i in 0..1000 {
+        sketch.observe(&(i % 10).to_string());
+    }
+    assert_eq!(sketch.estimate().round(), 10.0);
+    assert_eq!(HyperLogLog::with_precision(4)?.estimate(), 0.0);
+
+    assert!(HyperLogLog::with_precision(3).is_err());
+    assert!(HyperLogLog::with_precision(19).is_err());
+    Ok(())
+}
+
+#[test]
+fn test_space_saving() -> Result<(), failure::Error> {
+    // Key i appears 120 / i times, shuffled by interleaving.
+    let keys: Vec<(String, u64)> = (1..=40).map(|i| (i.to_string(), 120 / i)).collect();
+    let mut sketch = SpaceSaving::with_capacity(10)?;
+    for round in 0..120 {
+        for (key, count) in &keys {
+            if round < *count {
+                sketch.observe(key);
+            }
+        }
+    }
+    // The counts always add up to the number of keys observed, and the
+    // ordered index matches the counters.
+    let total: u64 = keys.iter().map(|(_key, count)| count).sum();
+    assert_eq!(
+        sketch.counters.values().map(|c| c.count).sum::<u64>(),
+        total
+    );
+    let indexed: BTreeSet<(u64, Rc<str>)> = sketch
+        .counters
+        .iter()
+        .map(|(key, counter)| (counter.count, Rc::clone(key)))
+        .collect();
+    assert_eq!(sketch.by_count, indexed);
+    let top = sketch.top(3);
+    assert_eq!(
+        top.iter().map(|&(key, _)| key).collect::<Vec<&str>>(),
+        vec!["1", "2", "3"]
+    );
+    for (key, counter) in top {
+        let true_count = 120 / key.parse::<u64>()?;
+        assert!(counter.count - counter.error <= true_count && true_count <= counter.count);
+    }
+
+    // With a counter for every key, the counts are exact.
+    let mut sketch = SpaceSaving::with_capacity(3)?;
+    for key in &["b", "a", "b", "c", "b", "a"] {
+        sketch.observe(key);
+    }
+    assert_eq!(
+        sketch.top(5),
+        vec![
+            ("b", Counter { count: 3, error: 0 }),
+            ("a", Counter { count: 2, error: 0 }),
+            ("c", Counter { count: 1, error: 0 }),
+        ]
+    );
+    sketch.observe("d");
+    assert_eq!(sketch.top(1)[0], ("b", Counter { count: 3, error: 0 }));
+    assert!(sketch
+        .top(3)
+        .contains(&("d", Counter { count: 2, error: 1 })));
+
+    assert!(SpaceSaving::with_capacity(0).is_err());
+    Ok(())
+}
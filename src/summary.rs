@@ -1,7 +1,15 @@
 use std::fmt;
 
+use crate::inference::{self, ConfidenceInterval};
+use crate::summation::{self, Precision};
+
 pub trait Observer<'a, T: 'a> {
-    fn observe(&mut self, value: &T) -> Result<(), failure::Error>;
+    /// Observes a value with the given weight. Weights must be finite and
+    /// non-negative; values with zero weight are ignored.
+    fn observe_weighted(&mut self, value: &T, weight: f64) -> Result<(), failure::Error>;
+    fn observe(&mut self, value: &T) -> Result<(), failure::Error> {
+        self.observe_weighted(value, 1.0)
+    }
     fn observe_many(
         &mut self,
         mut values: impl Iterator<Item = &'a T>,
@@ -10,6 +18,51 @@ pub trait Observer<'a, T: 'a> {
     }
 }
 
+/// An observer that can forget values it has observed, such as the summary
+/// of a sliding window.
+pub trait Remover<'a, T: 'a>: Observer<'a, T> {
+    /// Removes a value previously observed with the given weight. Removing a
+    /// value that was never observed leaves the summary meaningless.
+    fn remove_weighted(&mut self, value: &T, weight: f64) -> Result<(), failure::Error>;
+    fn remove(&mut self, value: &T) -> Result<(), failure::Error> {
+        self.remove_weighted(value, 1.0)
+    }
+}
+
+/// How the weights of observations are interpreted when correcting for bias.
+///
+/// Frequency weights count repeated observations, so a value with weight 3 is
+/// the same as observing it three times. Reliability weights describe the
+/// relative importance of each observation, and the sample size used to
+/// correct for bias is Kish's effective sample size, (Σw)² / Σw².
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Weights {
+    Frequency,
+    Reliability,
+}
+
+impl Default for Weights {
+    fn default() -> Self {
+        Weights::Frequency
+    }
+}
+
+impl std::str::FromStr for Weights {
+    type Err = failure::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "frequency" => Ok(Weights::Frequency),
+            "reliability" => Ok(Weights::Reliability),
+            _ => Err(crate::SamplersError::InvalidArgument {
+                name: "weight type".to_string(),
+                value: s.to_string(),
+            }
+            .into()),
+        }
+    }
+}
+
 /// An implementation of online updates for central moments.
 ///
 /// Functions prefixed with "population_" have no adjustment terms applied for
@@ -18,33 +71,116 @@ pub trait Observer<'a, T: 'a> {
 /// where applicable; these functions assume that the observed values are
 /// sampled from some distribution.
 ///
-/// `momentp` is the pth order central moment scaled by n. It is the sum of
-/// deviations from the mean taken to the pth power:
-/// $ \sum_{i=1}^n (x_i - \bar{x})^p $.
-#[derive(Debug, Default)]
-struct CentralMomentsSummary {
+/// `momentp` is the pth order central moment scaled by the total weight W. It
+/// is the weighted sum of deviations from the mean taken to the pth power:
+/// $ \sum_{i=1}^n w_i (x_i - \bar{x})^p $. Without weights, W = n.
+///
+/// At high precision, the rounding errors of the updates to the mean and the
+/// moments are carried in `compensation`, in that order, and the accumulated
+/// value of each is the sum of the field and its compensation.
+#[derive(Clone, Debug, Default)]
+pub struct CentralMomentsSummary {
     count: u64,
+    weights: Weights,
+    precision: Precision,
+    weight_sum: f64,
+    weight_sum2: f64,
     mean: Option<f64>,
     moment2: Option<f64>,
     moment3: Option<f64>,
     moment4: Option<f64>,
+    compensation: [f64; 4],
 }
 
 impl CentralMomentsSummary {
-    fn n(&self) -> f64 {
-        self.count as f64
+    pub fn with_weights(weights: Weights) -> CentralMomentsSummary {
+        CentralMomentsSummary {
+            weights,
+            ..CentralMomentsSummary::default()
+        }
+    }
+
+    pub fn with_precision(self, precision: Precision) -> CentralMomentsSummary {
+        CentralMomentsSummary { precision, ..self }
+    }
+
+    /// An empty summary with the same weights and precision.
+    fn cleared(&self) -> CentralMomentsSummary {
+        CentralMomentsSummary::with_weights(self.weights).with_precision(self.precision)
+    }
+
+    fn moment2(&self) -> Option<f64> {
+        Some(self.moment2? + self.compensation[1])
+    }
+
+    fn moment3(&self) -> Option<f64> {
+        Some(self.moment3? + self.compensation[2])
+    }
+
+    fn moment4(&self) -> Option<f64> {
+        Some(self.moment4? + self.compensation[3])
+    }
+
+    /// Adds the increments of the mean and the moments, in that order.
+    fn accumulate(&mut self, increments: [f64; 4]) {
+        let precision = self.precision;
+        let mut sums = [
+            &mut self.mean,
+            &mut self.moment2,
+            &mut self.moment3,
+            &mut self.moment4,
+        ];
+        for ((sum, compensation), increment) in sums
+            .iter_mut()
+            .zip(self.compensation.iter_mut())
+            .zip(increments.iter())
+        {
+            let sum = sum.get_or_insert(0.0);
+            match precision {
+                Precision::Standard => *sum += increment,
+                Precision::High => summation::compensated_add(sum, compensation, *increment),
+            }
+        }
+    }
+
+    /// Replaces the moments, discarding their compensation, and adds the
+    /// increment of the mean, which keeps its compensation.
+    fn replace_moments(&mut self, mean_increment: f64, moment2: f64, moment3: f64, moment4: f64) {
+        self.moment2 = Some(moment2);
+        self.moment3 = Some(moment3);
+        self.moment4 = Some(moment4);
+        for compensation in self.compensation[1..].iter_mut() {
+            *compensation = 0.0;
+        }
+        self.accumulate([mean_increment, 0.0, 0.0, 0.0]);
+    }
+
+    /// The sample size used to correct for bias.
+    fn effective_n(&self) -> f64 {
+        match self.weights {
+            Weights::Frequency => self.weight_sum,
+            Weights::Reliability => self.weight_sum * self.weight_sum / self.weight_sum2,
+        }
     }
 
     pub fn count(&self) -> u64 {
         self.count
     }
 
+    pub fn total_weight(&self) -> f64 {
+        self.weight_sum
+    }
+
     pub fn mean(&self) -> Option<f64> {
-        self.mean
+        Some(self.mean? + self.compensation[0])
     }
 
     pub fn variance(&self) -> Option<f64> {
-        Some(self.moment2? / (self.n() - 1.0))
+        let denominator = match self.weights {
+            Weights::Frequency => self.weight_sum - 1.0,
+            Weights::Reliability => self.weight_sum - self.weight_sum2 / self.weight_sum,
+        };
+        Some(self.moment2()? / denominator)
     }
 
     pub fn standard_deviation(&self) -> Option<f64> {
@@ -52,25 +188,21 @@ impl CentralMomentsSummary {
     }
 
     pub fn skewness(&self) -> Option<f64> {
-        Some(
-            self.n() * (self.n() - 1.0).sqrt() * self.moment3?
-                / (self.n() - 2.0)
-                / self.moment2?.powf(1.5),
-        )
+        let n = self.effective_n();
+        Some(self.population_skewness()? * (n * (n - 1.0)).sqrt() / (n - 2.0))
     }
 
     pub fn kurtosis(&self) -> Option<f64> {
+        let n = self.effective_n();
         Some(
-            (self.n() + 1.0) * self.n() * (self.n() - 1.0) / (self.n() - 2.0) / (self.n() - 3.0)
-                * self.moment4?
-                / self.moment2?.powi(2)
-                - 3.0 * (self.n() - 1.0).powi(2) / (self.n() - 2.0) / (self.n() - 3.0)
+            (n + 1.0) * (n - 1.0) / (n - 2.0) / (n - 3.0) * self.population_kurtosis()?
+                - 3.0 * (n - 1.0).powi(2) / (n - 2.0) / (n - 3.0)
                 + 3.0,
         )
     }
 
     pub fn population_variance(&self) -> Option<f64> {
-        Some(self.moment2? / self.n())
+        Some(self.moment2()? / self.weight_sum)
     }
 
     pub fn population_standard_deviation(&self) -> Option<f64> {
@@ -78,30 +210,165 @@ impl CentralMomentsSummary {
     }
 
     pub fn population_skewness(&self) -> Option<f64> {
-        Some((self.n()).sqrt() * self.moment3? / self.moment2?.powf(1.5))
+        Some(self.weight_sum.sqrt() * self.moment3()? / self.moment2()?.powf(1.5))
     }
 
     pub fn population_kurtosis(&self) -> Option<f64> {
-        Some((self.n()) * self.moment4? / self.moment2?.powi(2))
+        Some(self.weight_sum * self.moment4()? / self.moment2()?.powi(2))
+    }
+
+    pub fn mean_confidence_interval(&self, level: f64) -> Option<ConfidenceInterval> {
+        inference::mean_interval(self.effective_n(), self.mean()?, self.variance()?, level)
+    }
+
+    pub fn variance_confidence_interval(&self, level: f64) -> Option<ConfidenceInterval> {
+        inference::variance_interval(self.effective_n(), self.variance()?, level)
+    }
+
+    /// Combines the central moments of two disjoint sets of observations, as
+    /// if every value observed by `other` had been observed by `self`.
+    ///
+    /// Uses the pairwise update formulas from Pébay, "Formulas for Robust,
+    /// One-Pass Parallel Computation of Covariances and Arbitrary-Order
+    /// Statistical Moments" (2008), with the counts replaced by total weights.
+    pub fn merge(&mut self, other: &CentralMomentsSummary) {
+        if other.count == 0 {
+            return;
+        }
+        if self.count == 0 {
+            *self = CentralMomentsSummary {
+                weights: self.weights,
+                precision: self.precision,
+                ..other.clone()
+            };
+            return;
+        }
+        let (na, nb) = (self.weight_sum, other.weight_sum);
+        let n = na + nb;
+        let (m2a, m2b) = (
+            self.moment2().unwrap_or_default(),
+            other.moment2().unwrap_or_default(),
+        );
+        let (m3a, m3b) = (
+            self.moment3().unwrap_or_default(),
+            other.moment3().unwrap_or_default(),
+        );
+        let (m4a, m4b) = (
+            self.moment4().unwrap_or_default(),
+            other.moment4().unwrap_or_default(),
+        );
+        // As in `observe_weighted`, the compensations are subtracted
+        // separately from the means.
+        let delta = (other.mean.unwrap_or_default() - self.mean.unwrap_or_default())
+            + (other.compensation[0] - self.compensation[0]);
+        let delta2 = delta * delta;
+        self.count += other.count;
+        self.weight_sum += other.weight_sum;
+        self.weight_sum2 += other.weight_sum2;
+        self.replace_moments(
+            delta * nb / n,
+            m2a + m2b + delta2 * na * nb / n,
+            m3a + m3b
+                + delta * delta2 * na * nb * (na - nb) / (n * n)
+                + 3.0 * delta * (na * m2b - nb * m2a) / n,
+            m4a + m4b
+                + delta2 * delta2 * na * nb * (na * na - na * nb + nb * nb) / (n * n * n)
+                + 6.0 * delta2 * (na * na * m2b + nb * nb * m2a) / (n * n)
+                + 4.0 * delta * (na * m3b - nb * m3a) / n,
+        );
+    }
+}
+
+/// Returns an error unless the weight is finite and non-negative.
+pub(crate) fn validate_weight(weight: f64) -> Result<(), failure::Error> {
+    if weight.is_finite() && weight >= 0.0 {
+        Ok(())
+    } else {
+        Err(crate::SamplersError::InvalidArgument {
+            name: "weight".to_string(),
+            value: weight.to_string(),
+        }
+        .into())
     }
 }
 
 impl Observer<'_, f64> for CentralMomentsSummary {
-    fn observe(&mut self, &value: &f64) -> Result<(), failure::Error> {
+    /// Merges a single value with weight w into the summary. This is the
+    /// pairwise update with the second set's higher moments equal to zero.
+    fn observe_weighted(&mut self, &value: &f64, weight: f64) -> Result<(), failure::Error> {
+        validate_weight(weight)?;
+        if weight == 0.0 {
+            return Ok(());
+        }
+        let previous_weight = self.weight_sum;
         self.count += 1;
-        let delta = value - self.mean.unwrap_or_default();
-        let delta_n = delta / self.count as f64;
-        let delta2 = delta * delta;
-        let delta_n2 = delta_n * delta_n;
-        let mean = self.mean.get_or_insert(0.0);
-        let moment2 = self.moment2.get_or_insert(0.0);
-        let moment3 = self.moment3.get_or_insert(0.0);
-        let moment4 = self.moment4.get_or_insert(0.0);
-        *mean += delta_n;
-        *moment2 += delta * (delta - delta_n);
-        *moment3 += -3.0 * delta_n * *moment2 + delta * (delta2 - delta_n2);
-        *moment4 += -4.0 * delta_n * *moment3 - 6.0 * delta_n2 * *moment2
-            + delta * (delta * delta2 - delta_n * delta_n2);
+        self.weight_sum += weight;
+        self.weight_sum2 += weight * weight;
+        let total_weight = self.weight_sum;
+        // The compensation is subtracted separately, since rounding the mean
+        // would lose it when the mean is large.
+        let delta = (value - self.mean.unwrap_or_default()) - self.compensation[0];
+        let delta_w = delta * weight / total_weight;
+        let delta_w2 = delta_w * delta_w;
+        // The contribution of the new value to moment2.
+        let term = delta * delta_w * previous_weight;
+        let moment2 = self.moment2().unwrap_or_default();
+        let moment3 = self.moment3().unwrap_or_default();
+        self.accumulate([
+            delta_w,
+            term,
+            term * delta_w * (previous_weight - weight) / weight - 3.0 * delta_w * moment2,
+            term * delta_w2
+                * (previous_weight * previous_weight - previous_weight * weight + weight * weight)
+                / (weight * weight)
+                + 6.0 * delta_w2 * moment2
+                - 4.0 * delta_w * moment3,
+        ]);
+        Ok(())
+    }
+}
+
+impl Remover<'_, f64> for CentralMomentsSummary {
+    /// Splits a single value with weight w out of the summary. This solves
+    /// the pairwise update for the moments of the remaining values.
+    fn remove_weighted(&mut self, &value: &f64, weight: f64) -> Result<(), failure::Error> {
+        validate_weight(weight)?;
+        if weight == 0.0 {
+            return Ok(());
+        }
+        if self.count <= 1 || self.weight_sum <= weight {
+            *self = self.cleared();
+            return Ok(());
+        }
+        let total_weight = self.weight_sum;
+        let remaining_weight = total_weight - weight;
+        let deviation = (value - self.mean.unwrap_or_default()) - self.compensation[0];
+        // The deviation of the value from the mean of the remaining values.
+        let delta = deviation * total_weight / remaining_weight;
+        let delta_w = delta * weight / total_weight;
+        let delta_w2 = delta_w * delta_w;
+        let term = delta * delta_w * remaining_weight;
+        // Rounding may leave a tiny negative sum of squares.
+        let moment2 = (self.moment2().unwrap_or_default() - term).max(0.0);
+        let moment3 = self.moment3().unwrap_or_default()
+            - (term * delta_w * (remaining_weight - weight) / weight - 3.0 * delta_w * moment2);
+        let moment4 = self.moment4().unwrap_or_default()
+            - (term
+                * delta_w2
+                * (remaining_weight * remaining_weight - remaining_weight * weight
+                    + weight * weight)
+                / (weight * weight)
+                + 6.0 * delta_w2 * moment2
+                - 4.0 * delta_w * moment3);
+        self.count -= 1;
+        self.weight_sum = remaining_weight;
+        self.weight_sum2 -= weight * weight;
+        self.replace_moments(
+            -weight * deviation / remaining_weight,
+            moment2,
+            moment3,
+            moment4,
+        );
         Ok(())
     }
 }
@@ -141,14 +408,214 @@ fn test_central_moments_summary() -> Result<(), failure::Error> {
     Ok(())
 }
 
-#[derive(Debug, Default)]
+#[test]
+fn test_central_moments_summary_merge() -> Result<(), failure::Error> {
+    fn approx_eq(a: Option<f64>, b: Option<f64>) -> bool {
+        const THRESHOLD: f64 = 1e-9;
+        (a.unwrap() - b.unwrap()).abs() < THRESHOLD
+    }
+
+    let values = [-1.25, 6.25, 16.0, -6.25, 1.25, 8.0, 3.5];
+    let mut whole = CentralMomentsSummary::default();
+    whole.observe_many(values.iter())?;
+    for split in 0..=values.len() {
+        let mut left = CentralMomentsSummary::default();
+        left.observe_many(values[..split].iter())?;
+        let mut right = CentralMomentsSummary::default();
+        right.observe_many(values[split..].iter())?;
+        left.merge(&right);
+        assert_eq!(left.count(), whole.count());
+        assert!(approx_eq(left.mean(), whole.mean()));
+        assert!(approx_eq(left.variance(), whole.variance()));
+        assert!(approx_eq(left.skewness(), whole.skewness()));
+        assert!(approx_eq(left.kurtosis(), whole.kurtosis()));
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_central_moments_summary_weighted() -> Result<(), failure::Error> {
+    fn approx_eq(a: Option<f64>, b: Option<f64>) -> bool {
+        const THRESHOLD: f64 = 1e-9;
+        (a.unwrap() - b.unwrap()).abs() < THRESHOLD
+    }
+
+    // Integral frequency weights match repeating each value.
+    let values = [-1.25, 6.25, 16.0, -6.25, 1.25, 8.0];
+    let weights = [1.0, 3.0, 2.0, 1.0, 4.0, 0.0];
+    let mut weighted = CentralMomentsSummary::default();
+    let mut repeated = CentralMomentsSummary::default();
+    for (value, &weight) in values.iter().zip(weights.iter()) {
+        weighted.observe_weighted(value, weight)?;
+        for _ in 0..weight as usize {
+            repeated.observe(value)?;
+        }
+    }
+    assert_eq!(weighted.count(), 5);
+    assert_eq!(weighted.total_weight(), 11.0);
+    assert!(approx_eq(weighted.mean(), repeated.mean()));
+    assert!(approx_eq(weighted.variance(), repeated.variance()));
+    assert!(approx_eq(weighted.skewness(), repeated.skewness()));
+    assert!(approx_eq(weighted.kurtosis(), repeated.kurtosis()));
+    assert!(approx_eq(
+        weighted.population_kurtosis(),
+        repeated.population_kurtosis()
+    ));
+
+    // Scaling reliability weights changes nothing, and equal reliability
+    // weights match no weights at all.
+    let mut reliability = CentralMomentsSummary::with_weights(Weights::Reliability);
+    let mut scaled = CentralMomentsSummary::with_weights(Weights::Reliability);
+    for (value, &weight) in values.iter().zip(weights.iter()) {
+        reliability.observe_weighted(value, weight)?;
+        scaled.observe_weighted(value, weight * 0.1)?;
+    }
+    assert!(approx_eq(reliability.variance(), scaled.variance()));
+    assert!(approx_eq(reliability.kurtosis(), scaled.kurtosis()));
+    // Matches the unbiased weighted variance Σw(x - x̄)² / (W - Σw²/W).
+    assert!(approx_eq(reliability.variance(), Some(56.7625)));
+    let mut equal = CentralMomentsSummary::with_weights(Weights::Reliability);
+    let mut unweighted = CentralMomentsSummary::default();
+    for value in values.iter() {
+        equal.observe_weighted(value, 0.5)?;
+        unweighted.observe(value)?;
+    }
+    assert!(approx_eq(equal.variance(), unweighted.variance()));
+    assert!(approx_eq(equal.skewness(), unweighted.skewness()));
+
+    assert!(weighted.observe_weighted(&1.0, -1.0).is_err());
+    assert!(weighted.observe_weighted(&1.0, std::f64::NAN).is_err());
+    Ok(())
+}
+
+#[test]
+fn test_central_moments_summary_remove() -> Result<(), failure::Error> {
+    fn approx_eq(a: Option<f64>, b: Option<f64>) -> bool {
+        const THRESHOLD: f64 = 1e-9;
+        (a.unwrap() - b.unwrap()).abs() < THRESHOLD
+    }
+
+    // Removing values matches never having observed them.
+    let values = [-1.25, 6.25, 16.0, -6.25, 1.25, 8.0, 3.5];
+    let mut window = CentralMomentsSummary::default();
+    window.observe_many(values.iter())?;
+    window.observe_weighted(&9.0, 2.5)?;
+    window.remove_weighted(&9.0, 2.5)?;
+    // Kurtosis needs at least four values.
+    for split in 1..values.len() - 3 {
+        window.remove(&values[split - 1])?;
+        let mut expected = CentralMomentsSummary::default();
+        expected.observe_many(values[split..].iter())?;
+        assert_eq!(window.count(), expected.count());
+        assert!(approx_eq(window.mean(), expected.mean()));
+        assert!(approx_eq(window.variance(), expected.variance()));
+        assert!(approx_eq(window.skewness(), expected.skewness()));
+        assert!(approx_eq(window.kurtosis(), expected.kurtosis()));
+    }
+
+    let mut single = CentralMomentsSummary::default();
+    single.observe(&1.0)?;
+    single.remove(&1.0)?;
+    assert_eq!(single.count(), 0);
+    assert_eq!(single.mean(), None);
+    Ok(())
+}
+
+#[test]
+fn test_central_moments_summary_precision() -> Result<(), failure::Error> {
+    /// The exact mean and population variance of values that are all
+    /// multiples of 2⁻⁸ below 2⁴⁰ in magnitude, computed in integers and
+    /// rounded once at the end.
+    fn exact_moments(values: &[f64]) -> (f64, f64) {
+        const SCALE: f64 = 256.0;
+        let (mut sum, mut sum2): (i128, i128) = (0, 0);
+        for value in values {
+            let scaled = value * SCALE;
+            assert!(scaled.fract() == 0.0 && scaled.abs() < 2f64.powi(48));
+            sum += scaled as i128;
+            sum2 += (scaled as i128) * (scaled as i128);
+        }
+        let n = values.len() as i128;
+        let mean = sum as f64 / (n as f64 * SCALE);
+        let variance = (n * sum2 - sum * sum) as f64 / ((n * n) as f64 * SCALE * SCALE);
+        (mean, variance)
+    }
+    fn relative_errors(values: &[f64], precision: Precision) -> Result<(f64, f64), failure::Error> {
+        let mut summary = CentralMomentsSummary::default().with_precision(precision);
+        summary.observe_many(values.iter())?;
+        let (mean, variance) = exact_moments(values);
+        Ok((
+            ((summary.mean().unwrap() - mean) / mean).abs(),
+            ((summary.population_variance().unwrap() - variance) / variance).abs(),
+        ))
+    }
+
+    // A large offset with a small spread, repeated many times.
+    let offset: Vec<f64> = (0..200_000)
+        .map(|i| 1e9 + [0.5, -0.25, 0.125, -0.375, 0.0625][i % 5])
+        .collect();
+    // Values of very different magnitudes, largest first.
+    let mut mixed: Vec<f64> = (0..100_000)
+        .map(|i| {
+            if i % 100 == 0 {
+                2f64.powi(35)
+            } else {
+                (i % 7) as f64 * 0.0078125
+            }
+        })
+        .collect();
+    mixed.sort_by(|a, b| b.partial_cmp(a).unwrap());
+    // A repeated slow ramp, so the mean moves with every value.
+    let trend: Vec<f64> = (0..100_000)
+        .map(|i| 1e9 + (i % 1000) as f64 * 0.00390625)
+        .collect();
+    for values in [&offset, &mixed, &trend].iter() {
+        let (standard_mean, standard_variance) = relative_errors(values, Precision::Standard)?;
+        let (high_mean, high_variance) = relative_errors(values, Precision::High)?;
+        assert!(high_mean <= 2.0 * std::f64::EPSILON && high_variance <= 2.0 * std::f64::EPSILON);
+        assert!(high_mean <= standard_mean && high_variance <= standard_variance);
+    }
+    // Plain updates drift in the eighth significant digit of the variance.
+    assert!(relative_errors(&offset, Precision::Standard)?.1 > 1e-8);
+
+    // Merging and removing keep the accumulated compensation.
+    let mut left = CentralMomentsSummary::default().with_precision(Precision::High);
+    left.observe_many(offset[..1000].iter())?;
+    let mut right = CentralMomentsSummary::default().with_precision(Precision::High);
+    right.observe_many(offset[1000..2000].iter())?;
+    left.merge(&right);
+    left.remove(&offset[0])?;
+    let (mean, variance) = exact_moments(&offset[1..2000]);
+    assert!((left.mean().unwrap() - mean).abs() <= 2.0 * std::f64::EPSILON * mean);
+    assert!((left.population_variance().unwrap() - variance).abs() <= 1e-12 * variance);
+    Ok(())
+}
+
+#[derive(Clone, Debug, Default)]
 pub struct DistributionSummary {
     min: Option<f64>,
     max: Option<f64>,
+    // Whether any observed value was something other than 0 or 1.
+    non_binary: bool,
     central_moments_summary: CentralMomentsSummary,
 }
 
 impl DistributionSummary {
+    pub fn with_weights(weights: Weights) -> DistributionSummary {
+        DistributionSummary {
+            central_moments_summary: CentralMomentsSummary::with_weights(weights),
+            ..DistributionSummary::default()
+        }
+    }
+
+    pub fn with_precision(self, precision: Precision) -> DistributionSummary {
+        DistributionSummary {
+            central_moments_summary: self.central_moments_summary.with_precision(precision),
+            ..self
+        }
+    }
+
     pub fn min(&self) -> Option<f64> {
         self.min
     }
@@ -161,6 +628,12 @@ impl DistributionSummary {
         self.central_moments_summary.count()
     }
 
+    /// The sum of the weights of the observed values, which is the count if
+    /// no weights were given.
+    pub fn total_weight(&self) -> f64 {
+        self.central_moments_summary.total_weight()
+    }
+
     pub fn mean(&self) -> Option<f64> {
         self.central_moments_summary.mean()
     }
@@ -196,19 +669,70 @@ impl DistributionSummary {
     pub fn population_kurtosis(&self) -> Option<f64> {
         self.central_moments_summary.population_kurtosis()
     }
+
+    /// Whether every observed value is 0 or 1, so the mean is a proportion.
+    pub fn is_binary(&self) -> bool {
+        self.count() > 0 && !self.non_binary
+    }
+
+    pub fn mean_confidence_interval(&self, level: f64) -> Option<ConfidenceInterval> {
+        self.central_moments_summary.mean_confidence_interval(level)
+    }
+
+    pub fn variance_confidence_interval(&self, level: f64) -> Option<ConfidenceInterval> {
+        self.central_moments_summary
+            .variance_confidence_interval(level)
+    }
+
+    /// The Wilson score interval for the proportion of ones, if every observed
+    /// value is 0 or 1.
+    pub fn proportion_confidence_interval(&self, level: f64) -> Option<ConfidenceInterval> {
+        if !self.is_binary() {
+            return None;
+        }
+        inference::wilson_interval(
+            self.central_moments_summary.effective_n(),
+            self.mean()?,
+            level,
+        )
+    }
+
+    /// Combines two summaries of disjoint sets of observations.
+    pub fn merge(&mut self, other: &DistributionSummary) {
+        self.non_binary |= other.non_binary;
+        self.min = match (self.min, other.min) {
+            (Some(a), Some(b)) => Some(a.min(b)),
+            (a, b) => a.or(b),
+        };
+        self.max = match (self.max, other.max) {
+            (Some(a), Some(b)) => Some(a.max(b)),
+            (a, b) => a.or(b),
+        };
+        self.central_moments_summary
+            .merge(&other.central_moments_summary);
+    }
 }
 
 impl Observer<'_, f64> for DistributionSummary {
-    fn observe(&mut self, &value: &f64) -> Result<(), failure::Error> {
+    fn observe_weighted(&mut self, &value: &f64, weight: f64) -> Result<(), failure::Error> {
+        validate_weight(weight)?;
+        if weight == 0.0 {
+            return Ok(());
+        }
         self.min = Some(self.min.map_or(value, |min| min.min(value)));
         self.max = Some(self.max.map_or(value, |max| max.max(value)));
-        self.central_moments_summary.observe(&value)?;
+        self.non_binary |= value != 0.0 && value != 1.0;
+        self.central_moments_summary
+            .observe_weighted(&value, weight)?;
         Ok(())
     }
 }
 
 impl fmt::Display for DistributionSummary {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.total_weight() != self.count() as f64 {
+            writeln!(f, "Total weight: {}", self.total_weight())?;
+        }
         write!(
             f,
             "Count: {}\nMinimum: {}\nMaximum: {}\nMean: {}\nVariance: {}\nStandard deviation: \
@@ -242,6 +766,14 @@ fn test_distribution_summary() -> Result<(), failure::Error> {
     summary.observe(&-1.5)?;
     assert_eq!(summary.min(), Some(-1.5));
     assert_eq!(summary.max(), Some(8.25));
+    assert!(!summary.is_binary());
+    assert_eq!(summary.proportion_confidence_interval(0.95), None);
+
+    let mut summary = DistributionSummary::default();
+    assert!(!summary.is_binary());
+    summary.observe_many([0.0, 1.0, 1.0, 0.0].iter())?;
+    assert!(summary.is_binary());
+    assert!(summary.proportion_confidence_interval(0.95).is_some());
     Ok(())
 }
 
@@ -308,6 +840,154 @@ fn test_mean() {
     assert_eq!(mean(values.iter().cloned()), (1.3));
 }
 
+/// The mean of values that may fail to be read or transformed, which is an
+/// error for no values at all.
+fn nonempty_mean(
+    values: impl Iterator<Item = Result<f64, failure::Error>>,
+    name: &str,
+) -> Result<f64, failure::Error> {
+    let mut count = 0;
+    let mean = mean_result(values.inspect(|_| count += 1))?;
+    if count == 0 {
+        return Err(crate::SamplersError::CouldNotCalculateSummaryStatistic {
+            name: name.to_string(),
+        }
+        .into());
+    }
+    Ok(mean)
+}
+
+/// Returns an error unless the value is positive.
+pub fn positive(value: f64) -> Result<f64, failure::Error> {
+    if value > 0.0 {
+        Ok(value)
+    } else {
+        Err(crate::SamplersError::NonPositiveValue { value }.into())
+    }
+}
+
+/// The geometric mean, exp(mean(ln x)), which is the average of ratios such
+/// as speedups. Values must be positive.
+pub fn geometric_mean(values: impl Iterator<Item = f64>) -> Result<f64, failure::Error> {
+    let log_mean = nonempty_mean(
+        values.map(|value| positive(value).map(f64::ln)),
+        "geometric mean",
+    )?;
+    Ok(log_mean.exp())
+}
+
+/// The harmonic mean, 1 / mean(1 / x), which is the average of rates over
+/// equal amounts of work. Values must be positive.
+pub fn harmonic_mean(values: impl Iterator<Item = f64>) -> Result<f64, failure::Error> {
+    let reciprocal_mean = nonempty_mean(
+        values.map(|value| positive(value).map(f64::recip)),
+        "harmonic mean",
+    )?;
+    Ok(reciprocal_mean.recip())
+}
+
+/// The generalized mean with exponent p, mean(x^p)^(1/p). This is the
+/// harmonic mean for p = -1, the geometric mean in the limit p → 0, the
+/// arithmetic mean for p = 1 and the root mean square for p = 2, and tends to
+/// the minimum or maximum as p → ∓∞. Values must be positive.
+pub fn power_mean(values: impl Iterator<Item = f64>, p: f64) -> Result<f64, failure::Error> {
+    let name = format!("power mean with exponent {}", p);
+    if p.is_nan() {
+        return Err(crate::SamplersError::InvalidArgument {
+            name: "exponent".to_string(),
+            value: p.to_string(),
+        }
+        .into());
+    }
+    if p == 0.0 {
+        return geometric_mean(values);
+    }
+    if p.is_infinite() {
+        let extreme = values.map(positive).try_fold(None, |extreme, value| {
+            let value = value?;
+            Ok::<_, failure::Error>(Some(match extreme {
+                None => value,
+                Some(extreme) if p > 0.0 => value.max(extreme),
+                Some(extreme) => value.min(extreme),
+            }))
+        })?;
+        return extreme.ok_or_else(|| {
+            crate::SamplersError::CouldNotCalculateSummaryStatistic { name }.into()
+        });
+    }
+    let mean = nonempty_mean(
+        values.map(|value| positive(value).map(|v| v.powf(p))),
+        &name,
+    )?;
+    Ok(mean.powf(p.recip()))
+}
+
+/// The logarithm of the mean of exp(x), for values that are themselves
+/// logarithms, such as log-likelihoods. This is computed relative to the
+/// largest value so far, so that exp(x) never overflows.
+pub fn log_mean_exp(values: impl Iterator<Item = f64>) -> Result<f64, failure::Error> {
+    // The sum of exp(x - maximum) over the values so far.
+    let mut count = 0;
+    let mut maximum = std::f64::NEG_INFINITY;
+    let mut scaled_sum = 0.0;
+    for value in values {
+        count += 1;
+        if value > maximum {
+            scaled_sum = scaled_sum * (maximum - value).exp() + 1.0;
+            maximum = value;
+        } else {
+            scaled_sum += (value - maximum).exp();
+        }
+    }
+    if count == 0 {
+        return Err(crate::SamplersError::CouldNotCalculateSummaryStatistic {
+            name: "log-space mean".to_string(),
+        }
+        .into());
+    }
+    Ok(maximum + (scaled_sum / count as f64).ln())
+}
+
+#[test]
+fn test_alternative_means() -> Result<(), failure::Error> {
+    fn approx_eq(a: f64, b: f64) -> bool {
+        const THRESHOLD: f64 = 1e-12;
+        (a - b).abs() < THRESHOLD
+    }
+
+    let speedups = [2.0, 8.0, 0.5, 2.0];
+    let values = || speedups.iter().cloned();
+    assert!(approx_eq(geometric_mean(values())?, 2.0));
+    assert!(approx_eq(harmonic_mean(values())?, 4.0 / 3.125));
+    assert!(approx_eq(power_mean(values(), 1.0)?, mean(values())));
+    assert!(approx_eq(
+        power_mean(values(), -1.0)?,
+        harmonic_mean(values())?
+    ));
+    assert!(approx_eq(power_mean(values(), 0.0)?, 2.0));
+    assert!(approx_eq(
+        power_mean(values(), 2.0)?,
+        (72.25f64 / 4.0).sqrt()
+    ));
+    assert_eq!(power_mean(values(), std::f64::INFINITY)?, 8.0);
+    assert_eq!(power_mean(values(), std::f64::NEG_INFINITY)?, 0.5);
+
+    // exp(1000) overflows, but the log of its mean with exp(1000 - ln 3) does
+    // not.
+    let logs = [1000.0, 1000.0 - 3.0f64.ln()];
+    assert!(approx_eq(
+        log_mean_exp(logs.iter().cloned())?,
+        1000.0 + (2.0f64 / 3.0).ln()
+    ));
+
+    assert!(geometric_mean([1.0, 0.0].iter().cloned()).is_err());
+    assert!(harmonic_mean([1.0, -2.0].iter().cloned()).is_err());
+    assert!(geometric_mean([].iter().cloned()).is_err());
+    assert!(log_mean_exp([].iter().cloned()).is_err());
+    assert!(power_mean(values(), std::f64::NAN).is_err());
+    Ok(())
+}
+
 #[test]
 fn test_variance() {
     let values = [4.2, -0.8];
@@ -315,3 +995,153 @@ fn test_variance() {
     let values = [1.0, 2.0, 3.0, 4.0, 5.0, 6.0];
     assert_eq!(variance(values.iter().cloned()), (105.0 / 36.0, 3.5));
 }
+
+/// The pth quantile of sorted values, linearly interpolating between the two
+/// closest ranks. This matches the default of R and numpy.
+pub fn quantile(sorted_values: &[f64], p: f64) -> Option<f64> {
+    if sorted_values.is_empty() || !(0.0..=1.0).contains(&p) {
+        return None;
+    }
+    let rank = p * (sorted_values.len() - 1) as f64;
+    let lower = rank.floor() as usize;
+    let upper = rank.ceil() as usize;
+    let fraction = rank - lower as f64;
+    Some(sorted_values[lower] + fraction * (sorted_values[upper] - sorted_values[lower]))
+}
+
+#[test]
+fn test_quantile() {
+    let values = [1.0, 2.0, 3.0, 4.0, 5.0];
+    assert_eq!(quantile(&values, 0.0), Some(1.0));
+    assert_eq!(quantile(&values, 0.5), Some(3.0));
+    assert_eq!(quantile(&values, 0.9), Some(4.6));
+    assert_eq!(quantile(&values, 1.0), Some(5.0));
+    assert_eq!(quantile(&values[..1], 0.3), Some(1.0));
+    assert_eq!(quantile(&[], 0.5), None);
+    assert_eq!(quantile(&values, 1.5), None);
+}
+
+/// A statistic that can be estimated from a sample of values.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Statistic {
+    Mean,
+    Median,
+    Quantile(f64),
+    Variance,
+    StandardDeviation,
+    Skewness,
+    Kurtosis,
+}
+
+impl Statistic {
+    /// Evaluates the statistic over the values, which may be reordered.
+    pub fn evaluate(&self, values: &mut [f64]) -> Option<f64> {
+        match self {
+            Statistic::Median => Statistic::Quantile(0.5).evaluate(values),
+            Statistic::Quantile(p) => {
+                values.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+                quantile(values, *p)
+            }
+            _ => {
+                let mut summary = DistributionSummary::default();
+                summary.observe_many(values.iter()).ok()?;
+                match self {
+                    Statistic::Mean => summary.mean(),
+                    Statistic::Variance => summary.variance(),
+                    Statistic::StandardDeviation => summary.standard_deviation(),
+                    Statistic::Skewness => summary.skewness(),
+                    Statistic::Kurtosis => summary.kurtosis(),
+                    Statistic::Median | Statistic::Quantile(_) => unreachable!(),
+                }
+            }
+        }
+    }
+}
+
+impl std::str::FromStr for Statistic {
+    type Err = failure::Error;
+
+    /// Parses a statistic name. Quantiles are written as a percentile, such as
+    /// `p90` or `p99.9`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "mean" => Ok(Statistic::Mean),
+            "median" => Ok(Statistic::Median),
+            "variance" => Ok(Statistic::Variance),
+            "stddev" => Ok(Statistic::StandardDeviation),
+            "skewness" => Ok(Statistic::Skewness),
+            "kurtosis" => Ok(Statistic::Kurtosis),
+            _ => match s.strip_prefix('p').map(str::parse::<f64>) {
+                Some(Ok(percentile)) if (0.0..=100.0).contains(&percentile) => {
+                    Ok(Statistic::Quantile(percentile / 100.0))
+                }
+                _ => Err(crate::SamplersError::InvalidArgument {
+                    name: "statistic".to_string(),
+                    value: s.to_string(),
+                }
+                .into()),
+            },
+        }
+    }
+}
+
+impl fmt::Display for Statistic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Statistic::Mean => write!(f, "mean"),
+            Statistic::Median => write!(f, "median"),
+            Statistic::Quantile(p) => write!(f, "p{}", p * 100.0),
+            Statistic::Variance => write!(f, "variance"),
+            Statistic::StandardDeviation => write!(f, "stddev"),
+            Statistic::Skewness => write!(f, "skewness"),
+            Statistic::Kurtosis => write!(f, "kurtosis"),
+        }
+    }
+}
+
+#[test]
+fn test_statistic() -> Result<(), failure::Error> {
+    let values = [-1.25, 6.25, 16.0, -6.25, 1.25, 8.0];
+    let evaluate = |name: &str| -> Result<Option<f64>, failure::Error> {
+        Ok(name.parse::<Statistic>()?.evaluate(&mut values.clone()))
+    };
+    assert_eq!(evaluate("mean")?, Some(4.0));
+    assert_eq!(evaluate("median")?, Some(3.75));
+    assert_eq!(evaluate("p0")?, Some(-6.25));
+    assert_eq!(evaluate("p100")?, Some(16.0));
+    assert_eq!(evaluate("variance")?, Some(61.05));
+    assert!("p101".parse::<Statistic>().is_err());
+    assert!("mode".parse::<Statistic>().is_err());
+    Ok(())
+}
+
+/// The rank of each value among all of the values, starting from 1. Tied
+/// values are given the average of the ranks they span.
+pub fn ranks(values: &[f64]) -> Vec<f64> {
+    let mut order: Vec<usize> = (0..values.len()).collect();
+    order.sort_by(|&i, &j| {
+        values[i]
+            .partial_cmp(&values[j])
+            .unwrap_or(std::cmp::Ordering::Equal)
+    });
+    let mut ranks = vec![0.0; values.len()];
+    let mut start = 0;
+    while start < order.len() {
+        let mut end = start + 1;
+        while end < order.len() && values[order[end]] == values[order[start]] {
+            end += 1;
+        }
+        // Positions start..end share the average of ranks start+1..=end.
+        let rank = (start + end + 1) as f64 / 2.0;
+        order[start..end].iter().for_each(|&i| ranks[i] = rank);
+        start = end;
+    }
+    ranks
+}
+
+#[test]
+fn test_ranks() {
+    assert_eq!(ranks(&[3.0, 1.0, 2.0]), vec![3.0, 1.0, 2.0]);
+    assert_eq!(ranks(&[5.0, 1.0, 5.0, 2.0]), vec![3.5, 1.0, 3.5, 2.0]);
+    assert_eq!(ranks(&[]), Vec::<f64>::new());
+}
@@ -0,0 +1,143 @@
+use crate::SamplersError;
+
+/// How sums of floating-point values are accumulated.
+///
+/// Standard precision adds each value directly, so rounding errors grow with
+/// the number of values and with the magnitude of the running sum, except
+/// for totals that are only needed at the end, which are summed pairwise. High
+/// precision also carries the rounding error of every addition, which keeps
+/// the error bounded independently of the number of values at the cost of a
+/// few more operations per value.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Precision {
+    Standard,
+    High,
+}
+
+impl Default for Precision {
+    fn default() -> Self {
+        Precision::Standard
+    }
+}
+
+impl std::str::FromStr for Precision {
+    type Err = failure::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "standard" => Ok(Precision::Standard),
+            "high" => Ok(Precision::High),
+            _ => Err(SamplersError::InvalidArgument {
+                name: "precision".to_string(),
+                value: s.to_string(),
+            }
+            .into()),
+        }
+    }
+}
+
+/// Adds a value to a sum, accumulating the rounding error of the addition in
+/// `compensation`. The compensated sum is `sum + compensation`.
+///
+/// This is Neumaier's improvement of Kahan summation, which also recovers
+/// the error when the value is larger in magnitude than the sum.
+pub fn compensated_add(sum: &mut f64, compensation: &mut f64, value: f64) {
+    let total = *sum + value;
+    if sum.abs() >= value.abs() {
+        *compensation += (*sum - total) + value;
+    } else {
+        *compensation += (value - total) + *sum;
+    }
+    *sum = total;
+}
+
+/// The number of values summed directly before the sums are paired. Larger
+/// blocks are faster and barely less accurate, as in NumPy.
+const PAIRWISE_BLOCK_SIZE: u32 = 128;
+
+/// A sum of values that pairs equal-sized partial sums as the values arrive,
+/// as if they were added along a balanced binary tree.
+///
+/// The rounding error grows with the logarithm of the number of values rather
+/// than linearly, with no extra work per value and memory that also grows
+/// logarithmically. Unlike `compensated_add`, the total is only cheap to read
+/// at the end.
+#[derive(Debug, Default, Clone)]
+pub struct PairwiseSum {
+    block: f64,
+    block_len: u32,
+    // The partial sum of 2^k blocks at index k, if there is one.
+    partials: Vec<Option<f64>>,
+}
+
+impl PairwiseSum {
+    pub fn add(&mut self, value: f64) {
+        self.block += value;
+        self.block_len += 1;
+        if self.block_len < PAIRWISE_BLOCK_SIZE {
+            return;
+        }
+        let mut carry = std::mem::take(&mut self.block);
+        self.block_len = 0;
+        for partial in self.partials.iter_mut() {
+            match partial.take() {
+                Some(sum) => carry += sum,
+                None => {
+                    *partial = Some(carry);
+                    return;
+                }
+            }
+        }
+        self.partials.push(Some(carry));
+    }
+
+    /// The sum of the values added so far, adding the smallest partial sums
+    /// first.
+    pub fn total(&self) -> f64 {
+        self.partials
+            .iter()
+            .flatten()
+            .fold(self.block, |total, sum| total + sum)
+    }
+}
+
+#[test]
+fn test_compensated_sum() {
+    fn compensated_sum(values: &[f64]) -> f64 {
+        let (mut sum, mut compensation) = (0.0, 0.0);
+        for &value in values {
+            compensated_add(&mut sum, &mut compensation, value);
+        }
+        sum + compensation
+    }
+
+    // Every naive partial sum loses the ones.
+    let values = [1.0, 1e100, 1.0, -1e100];
+    assert_eq!(values.iter().sum::<f64>(), 0.0);
+    assert_eq!(compensated_sum(&values), 2.0);
+
+    // The exact sum of 10,000 copies of the double nearest 0.1 rounds to
+    // 1000, but the naive sum drifts away from it.
+    let tenths = vec![0.1; 10_000];
+    assert_ne!(tenths.iter().sum::<f64>(), 1000.0);
+    assert_eq!(compensated_sum(&tenths), 1000.0);
+
+    // Pairing bounds the drift without compensating for it.
+    let pairwise = |values: &[f64]| {
+        let mut sum = PairwiseSum::default();
+        values.iter().for_each(|&value| sum.add(value));
+        sum.total()
+    };
+    let tenths = vec![0.1; 1_000_000];
+    let naive_error = (tenths.iter().sum::<f64>() - 100_000.0).abs();
+    let pairwise_error = (pairwise(&tenths) - 100_000.0).abs();
+    assert!(pairwise_error < 1e-9 && pairwise_error * 1000.0 < naive_error);
+    assert_eq!(compensated_sum(&tenths), 100_000.0);
+    assert_eq!(pairwise(&[]), 0.0);
+    assert_eq!(pairwise(&values), 0.0);
+    let integers: Vec<f64> = (1..=1000).map(f64::from).collect();
+    assert_eq!(pairwise(&integers), 500_500.0);
+
+    assert_eq!("high".parse::<Precision>().unwrap(), Precision::High);
+    assert!("exact".parse::<Precision>().is_err());
+}
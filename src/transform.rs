@@ -0,0 +1,224 @@
+use crate::summary::{self, DistributionSummary};
+use crate::SamplersError;
+
+/// The range of λ searched for the maximum likelihood estimate of a power
+/// transformation.
+const LAMBDA_BOUNDS: (f64, f64) = (-10.0, 10.0);
+
+/// A transformation applied to each value independently.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Transform {
+    /// (x - mean) / standard deviation.
+    ZScore { mean: f64, standard_deviation: f64 },
+    /// (x - min) / (max - min), which maps the range to [0, 1].
+    MinMax { min: f64, max: f64 },
+    /// The natural logarithm, for positive values.
+    Log,
+    /// The Box–Cox power transformation with the given λ, for positive
+    /// values.
+    BoxCox(f64),
+    /// The Yeo–Johnson power transformation with the given λ, which extends
+    /// Box–Cox to zero and negative values.
+    YeoJohnson(f64),
+}
+
+impl Transform {
+    /// Standardizes values with the given mean and standard deviation, which
+    /// must be positive.
+    pub fn z_score(mean: f64, standard_deviation: f64) -> Result<Transform, failure::Error> {
+        if standard_deviation > 0.0 {
+            Ok(Transform::ZScore {
+                mean,
+                standard_deviation,
+            })
+        } else {
+            Err(SamplersError::CouldNotCalculateSummaryStatistic {
+                name: "standard deviation".to_string(),
+            }
+            .into())
+        }
+    }
+
+    /// Standardizes values with the mean and sample standard deviation of a
+    /// summary.
+    pub fn z_score_of(summary: &DistributionSummary) -> Result<Transform, failure::Error> {
+        Transform::z_score(
+            summary.mean().unwrap_or(std::f64::NAN),
+            summary.standard_deviation().unwrap_or(std::f64::NAN),
+        )
+    }
+
+    /// Rescales values with the given minimum and maximum, which must be
+    /// distinct.
+    pub fn min_max(min: f64, max: f64) -> Result<Transform, failure::Error> {
+        if min < max {
+            Ok(Transform::MinMax { min, max })
+        } else {
+            Err(SamplersError::CouldNotCalculateSummaryStatistic {
+                name: "range".to_string(),
+            }
+            .into())
+        }
+    }
+
+    /// Rescales values with the minimum and maximum of a summary.
+    pub fn min_max_of(summary: &DistributionSummary) -> Result<Transform, failure::Error> {
+        Transform::min_max(
+            summary.min().unwrap_or(std::f64::NAN),
+            summary.max().unwrap_or(std::f64::NAN),
+        )
+    }
+
+    pub fn apply(&self, value: f64) -> Result<f64, failure::Error> {
+        Ok(match *self {
+            Transform::ZScore {
+                mean,
+                standard_deviation,
+            } => (value - mean) / standard_deviation,
+            Transform::MinMax { min, max } => (value - min) / (max - min),
+            Transform::Log => summary::positive(value)?.ln(),
+            Transform::BoxCox(lambda) => box_cox(summary::positive(value)?, lambda),
+            Transform::YeoJohnson(lambda) => yeo_johnson(value, lambda),
+        })
+    }
+}
+
+/// (x^λ - 1) / λ, or ln(x) when λ is 0.
+pub fn box_cox(value: f64, lambda: f64) -> f64 {
+    if lambda == 0.0 {
+        value.ln()
+    } else {
+        (lambda * value.ln()).exp_m1() / lambda
+    }
+}
+
+/// The Box–Cox transformation of x + 1 for x ≥ 0, and the negated
+/// transformation of 1 - x with parameter 2 - λ for x < 0.
+pub fn yeo_johnson(value: f64, lambda: f64) -> f64 {
+    if value >= 0.0 {
+        box_cox(value + 1.0, lambda)
+    } else {
+        -box_cox(1.0 - value, 2.0 - lambda)
+    }
+}
+
+/// The maximum likelihood estimate of the Box–Cox λ, which makes the
+/// transformed values closest to normally distributed. This maximizes the
+/// profile log-likelihood (λ - 1) Σ ln x - n/2 ln σ̂², where σ̂² is the
+/// population variance of the transformed values, as `scipy.stats.boxcox`
+/// does.
+pub fn box_cox_lambda(values: &[f64]) -> Result<f64, failure::Error> {
+    let log_sum = values
+        .iter()
+        .map(|&value| Ok(summary::positive(value)?.ln()))
+        .sum::<Result<f64, failure::Error>>()?;
+    maximum_likelihood_lambda(values, |lambda| {
+        (lambda - 1.0) * log_sum
+            - values.len() as f64 / 2.0
+                * transformed_variance(values, |value| box_cox(value, lambda)).ln()
+    })
+}
+
+/// The maximum likelihood estimate of the Yeo–Johnson λ, which maximizes
+/// (λ - 1) Σ sign(x) ln(|x| + 1) - n/2 ln σ̂², as `scipy.stats.yeojohnson`
+/// does.
+pub fn yeo_johnson_lambda(values: &[f64]) -> Result<f64, failure::Error> {
+    let log_sum: f64 = values
+        .iter()
+        .map(|value| value.signum() * value.abs().ln_1p())
+        .sum();
+    maximum_likelihood_lambda(values, |lambda| {
+        (lambda - 1.0) * log_sum
+            - values.len() as f64 / 2.0
+                * transformed_variance(values, |value| yeo_johnson(value, lambda)).ln()
+    })
+}
+
+fn transformed_variance(values: &[f64], transform: impl Fn(f64) -> f64) -> f64 {
+    let (population_variance, _sample_variance) =
+        summary::variance(values.iter().map(|&value| transform(value)));
+    population_variance
+}
+
+/// Maximizes a log-likelihood of λ by golden-section search, which assumes
+/// that it has a single maximum within the bounds.
+fn maximum_likelihood_lambda(
+    values: &[f64],
+    log_likelihood: impl Fn(f64) -> f64,
+) -> Result<f64, failure::Error> {
+    if !values.windows(2).any(|pair| pair[0] != pair[1]) {
+        return Err(SamplersError::CouldNotCalculateSummaryStatistic {
+            name: "lambda".to_string(),
+        }
+        .into());
+    }
+    let ratio = (5f64.sqrt() - 1.0) / 2.0;
+    let (mut lower, mut upper) = LAMBDA_BOUNDS;
+    let mut left = upper - ratio * (upper - lower);
+    let mut right = lower + ratio * (upper - lower);
+    let (mut left_value, mut right_value) = (log_likelihood(left), log_likelihood(right));
+    while upper - lower > 1e-10 {
+        if left_value > right_value {
+            upper = right;
+            right = left;
+            right_value = left_value;
+            left = upper - ratio * (upper - lower);
+            left_value = log_likelihood(left);
+        } else {
+            lower = left;
+            left = right;
+            left_value = right_value;
+            right = lower + ratio * (upper - lower);
+            right_value = log_likelihood(right);
+        }
+    }
+    Ok((lower + upper) / 2.0)
+}
+
+#[test]
+fn test_transform() -> Result<(), failure::Error> {
+    use crate::summary::Observer;
+
+    fn approx_eq(a: f64, b: f64) -> bool {
+        const THRESHOLD: f64 = 1e-6;
+        (a - b).abs() < THRESHOLD
+    }
+
+    let mut summary = DistributionSummary::default();
+    summary.observe_many([2.0, 4.0, 4.0, 6.0].iter())?;
+    let z_score = Transform::z_score_of(&summary)?;
+    assert!(approx_eq(z_score.apply(6.0)?, 2.0 / (8.0f64 / 3.0).sqrt()));
+    let min_max = Transform::min_max_of(&summary)?;
+    assert_eq!(min_max.apply(5.0)?, 0.75);
+    assert!(Transform::Log.apply(0.0).is_err());
+
+    // Matches scipy.stats.boxcox and scipy.stats.yeojohnson.
+    let positive = [1.2, 0.5, 3.8, 2.2, 7.5, 1.1, 0.9, 4.4, 12.0, 2.7];
+    let lambda = box_cox_lambda(&positive)?;
+    assert!(approx_eq(lambda, -0.056698250));
+    assert!(approx_eq(
+        Transform::BoxCox(lambda).apply(1.2)?,
+        0.181382438
+    ));
+    assert!(approx_eq(box_cox(0.5, 0.0), 0.5f64.ln()));
+    let mixed = [1.2, -0.5, 3.8, -2.2, 7.5, 0.0, 0.9, -4.4, 12.0, 2.7];
+    let lambda = yeo_johnson_lambda(&mixed)?;
+    assert!(approx_eq(lambda, 0.762883916));
+    assert!(approx_eq(yeo_johnson(-2.2, lambda), -2.599818490));
+    assert!(approx_eq(yeo_johnson(1.2, lambda), 1.081234822));
+    assert!(approx_eq(yeo_johnson(-1.0, 2.0), -(2.0f64.ln())));
+
+    assert!(box_cox_lambda(&mixed).is_err());
+    assert!(yeo_johnson_lambda(&[3.0, 3.0]).is_err());
+    let mut constant = DistributionSummary::default();
+    constant.observe_many([1.0, 1.0].iter())?;
+    assert!(Transform::min_max_of(&constant).is_err());
+    assert!(Transform::z_score_of(&constant).is_err());
+    // Given statistics are checked in the same way.
+    assert_eq!(Transform::z_score(1.0, 2.0)?.apply(5.0)?, 2.0);
+    assert!(Transform::z_score(1.0, 0.0).is_err());
+    assert!(Transform::z_score(1.0, std::f64::NAN).is_err());
+    assert!(Transform::min_max(3.0, 3.0).is_err());
+    assert!(Transform::min_max(4.0, 3.0).is_err());
+    Ok(())
+}